@@ -0,0 +1,28 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+pub fn bench<T>(name: &str, samples: usize, mut routine: impl FnMut() -> T) -> Duration {
+    black_box(routine());
+    let mut timings: Vec<Duration> = (0..samples)
+        .map(|_| {
+            let started = Instant::now();
+            black_box(routine());
+            started.elapsed()
+        })
+        .collect();
+    timings.sort_unstable();
+    let median = timings[samples / 2];
+    println!(
+        "{:<32} median {:>12.3?} over {} samples",
+        name, median, samples
+    );
+    median
+}
+
+pub fn compare(baseline: Duration, candidate: Duration) {
+    println!(
+        "{:<32} {:.1}x",
+        "speedup",
+        baseline.as_secs_f64() / candidate.as_secs_f64()
+    );
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use aoc_2020::problems::day_15::Spoken;
+use std::collections::HashMap;
+
+const TURNS: usize = 30_000_000;
+
+fn spoken_with_hash_map(starting: &[u32], turns: usize) -> (u32, usize) {
+    let mut last_seen: HashMap<u32, u32> = HashMap::new();
+    let mut last = starting[0];
+    for turn in 1..turns as u32 {
+        let spoken = match starting.get(turn as usize) {
+            Some(number) => *number,
+            None => last_seen.get(&last).map_or(0, |seen| turn - seen),
+        };
+        last_seen.insert(last, turn);
+        last = spoken;
+    }
+    let bytes = last_seen.capacity() * (std::mem::size_of::<(u32, u32)>() + 1);
+    (last, bytes)
+}
+
+fn spoken_with_flat_table(starting: &[u32], turns: usize) -> (u32, usize) {
+    let mut spoken = Spoken::with_capacity(starting, turns);
+    let number = spoken.nth(turns - 1).unwrap();
+    (number, spoken.table_bytes())
+}
+
+fn main() {
+    let (number, map_bytes) = spoken_with_hash_map(&[0, 3, 6], TURNS);
+    let (flat_number, table_bytes) = spoken_with_flat_table(&[0, 3, 6], TURNS);
+    assert_eq!((number, flat_number), (175594, 175594));
+
+    println!(
+        "day 15 to turn {}: hash map about {} bytes, flat table {} bytes",
+        TURNS, map_bytes, table_bytes
+    );
+    let hash_map = common::bench("hash map", 5, || spoken_with_hash_map(&[0, 3, 6], TURNS));
+    let flat_table = common::bench("flat table", 5, || {
+        spoken_with_flat_table(&[0, 3, 6], TURNS)
+    });
+    common::compare(hash_map, flat_table);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use aoc_2020::problems::day_6::{
+    sum_of_of_unique_questions_per_group, sum_of_questions_to_which_every_one_answered_yes_in_group,
+};
+use std::collections::HashSet;
+
+fn survey(bytes: usize) -> String {
+    let mut seed: u64 = 2020;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) as usize
+    };
+    let mut survey = String::new();
+    while survey.len() < bytes {
+        for _ in 0..next() % 5 + 1 {
+            let first = next();
+            let person: String = (0..next() % 26 + 1)
+                .map(|offset| (b'a' + ((first + offset) % 26) as u8) as char)
+                .collect();
+            survey.push_str(&person);
+            survey.push('\n');
+        }
+        survey.push('\n');
+    }
+    survey
+}
+
+fn hash_set_anyone(questions_per_group: &str) -> usize {
+    questions_per_group
+        .split("\n\n")
+        .map(|group| {
+            group
+                .split_ascii_whitespace()
+                .flat_map(|person| person.chars())
+                .collect::<HashSet<char>>()
+                .len()
+        })
+        .sum()
+}
+
+fn hash_set_everyone(questions_per_group: &str) -> usize {
+    questions_per_group
+        .split("\n\n")
+        .map(|group| {
+            let mut people = group.split_ascii_whitespace();
+            let first = people.next().unwrap_or("");
+            let remaining: Vec<&str> = people.collect();
+            first
+                .chars()
+                .filter(|c| remaining.iter().all(|person| person.contains(*c)))
+                .count()
+        })
+        .sum()
+}
+
+fn main() {
+    let survey = survey(8 * 1024 * 1024);
+    let bitset = || {
+        (
+            sum_of_of_unique_questions_per_group(&survey).unwrap(),
+            sum_of_questions_to_which_every_one_answered_yes_in_group(&survey).unwrap(),
+        )
+    };
+    let hash_set = || (hash_set_anyone(&survey), hash_set_everyone(&survey));
+    assert_eq!(bitset(), hash_set());
+
+    println!("day 6 survey of {} bytes", survey.len());
+    let hash_set = common::bench("hash set", 10, hash_set);
+    let bitset = common::bench("bitset", 10, bitset);
+    common::compare(hash_set, bitset);
+}
@@ -1 +1 @@
-mod problems;
+pub mod problems;
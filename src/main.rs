@@ -1,21 +1,20 @@
-fn main() {
-    let input = "ab
-    ac";
-    println!("{:?}", foo(input))
-}
-
-fn foo(questions: &str) -> Vec<char> {
-    let mut questions = questions.split_ascii_whitespace();
-    let first_set = questions.next().unwrap();
-    let remaining: Vec<&str> = questions.collect();
+use aoc_2020::problems::day_8::{debugger, Program};
+use std::{env, fs, io, process};
 
-    first_set
-        .clone()
-        .chars()
-        .filter(|c| all_contains(&remaining, c))
-        .collect()
-}
+fn main() {
+    let path = env::args().nth(1).unwrap_or_else(|| {
+        eprintln!("usage: aoc-2020 <handheld program>");
+        process::exit(2);
+    });
+    let source = fs::read_to_string(&path).unwrap_or_else(|error| {
+        eprintln!("cannot read {}: {}", path, error);
+        process::exit(1);
+    });
+    let program = Program::parse(&source).unwrap_or_else(|error| {
+        eprintln!("cannot parse {}: {:?}", path, error);
+        process::exit(1);
+    });
 
-fn all_contains(vec: &Vec<&str>, character: &char) -> bool {
-    vec.into_iter().all(|v| v.contains(*character))
+    let stdin = io::stdin();
+    debugger::repl(&program, stdin.lock(), io::stdout()).expect("debugger I/O failed");
 }
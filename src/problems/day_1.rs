@@ -1,7 +1,11 @@
 fn pair_having_sum_n(n: i64, inputs: &Vec<i64>) -> Option<Vec<i64>> {
-    inputs.into_iter().find_map(|x| {
+    pair_in_window_having_sum_n(n, inputs)
+}
+
+pub(super) fn pair_in_window_having_sum_n(n: i64, window: &[i64]) -> Option<Vec<i64>> {
+    window.iter().enumerate().find_map(|(index, x)| {
         let remaining = n - x;
-        if inputs.contains(&remaining) {
+        if window[index + 1..].contains(&remaining) {
             Some(vec![*x, remaining])
         } else {
             None
@@ -41,6 +45,16 @@ mod tests {
         assert_eq!(pair, Some(vec![1721, 299]))
     }
     #[test]
+    fn pair_must_use_two_distinct_entries() {
+        let inputs = vec![1010, 979, 1041];
+
+        assert_eq!(
+            pair_in_window_having_sum_n(2020, &inputs),
+            Some(vec![979, 1041])
+        );
+        assert_eq!(pair_in_window_having_sum_n(2020, &inputs[..2]), None);
+    }
+    #[test]
     fn find_tuple_of_size_3_having_sum_2020() {
         let inputs = vec![1000, 979, 420, 299, 600, 1456];
         let pair = find_tuple_of_n_having_sum(3, 2020, &inputs);
@@ -0,0 +1,187 @@
+use std::fmt;
+
+const DIGITS_PER_LIMB: usize = 9;
+const LIMB_BASE: u32 = 1_000_000_000;
+
+// Arrangement counts outgrow u64 after about seventy adapters, so they are
+// kept as base 10^9 limbs, least significant first.
+#[derive(Debug, Clone, PartialEq)]
+struct Count {
+    limbs: Vec<u32>,
+}
+
+impl Count {
+    fn zero() -> Self {
+        Count { limbs: vec![0] }
+    }
+
+    fn one() -> Self {
+        Count { limbs: vec![1] }
+    }
+
+    fn add(&self, other: &Count) -> Count {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0;
+        for index in 0..self.limbs.len().max(other.limbs.len()) {
+            let sum =
+                self.limbs.get(index).unwrap_or(&0) + other.limbs.get(index).unwrap_or(&0) + carry;
+            limbs.push(sum % LIMB_BASE);
+            carry = sum / LIMB_BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+        Count { limbs }
+    }
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        write!(f, "{}", limbs.next().unwrap_or(&0))?;
+        limbs.try_for_each(|limb| write!(f, "{:0width$}", limb, width = DIGITS_PER_LIMB))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AdapterConfig {
+    max_step: u64,
+    device_offset: u64,
+}
+
+impl Default for AdapterConfig {
+    fn default() -> Self {
+        AdapterConfig {
+            max_step: 3,
+            device_offset: 3,
+        }
+    }
+}
+
+fn parse_adapters(input: &str) -> Option<Vec<u64>> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse::<u64>().ok())
+        .collect()
+}
+
+fn chain(adapters: &[u64], config: AdapterConfig) -> Vec<u64> {
+    let mut chain = adapters.to_vec();
+    chain.sort_unstable();
+    let device = chain.last().unwrap_or(&0) + config.device_offset;
+    chain.insert(0, 0);
+    chain.push(device);
+    chain
+}
+
+fn difference_distribution(adapters: &[u64], config: AdapterConfig) -> Option<Vec<usize>> {
+    let mut distribution = vec![0; config.max_step as usize + 1];
+    for pair in chain(adapters, config).windows(2) {
+        let difference = pair[1] - pair[0];
+        *distribution.get_mut(difference as usize)? += 1;
+    }
+    Some(distribution)
+}
+
+fn count_arrangements(adapters: &[u64], config: AdapterConfig) -> Count {
+    let chain = chain(adapters, config);
+    let mut arrangements = vec![Count::one()];
+
+    for (index, joltage) in chain.iter().enumerate().skip(1) {
+        let count = (0..index)
+            .rev()
+            .take_while(|previous| joltage - chain[*previous] <= config.max_step)
+            .fold(Count::zero(), |count, previous| {
+                count.add(&arrangements[previous])
+            });
+        arrangements.push(count);
+    }
+    arrangements.pop().unwrap()
+}
+
+fn removable_adapters(adapters: &[u64], config: AdapterConfig) -> Vec<u64> {
+    chain(adapters, config)
+        .windows(3)
+        .filter(|triple| triple[2] - triple[0] <= config.max_step)
+        .map(|triple| triple[1])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SMALL: &str = "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4";
+    const LARGE: &str = "28\n33\n18\n42\n31\n14\n46\n20\n48\n47\n24\n23\n49\n45\n19\n38
+39\n11\n1\n32\n25\n35\n8\n17\n7\n9\n4\n2\n34\n10\n3";
+
+    #[test]
+    fn count_joltage_differences() {
+        let config = AdapterConfig::default();
+
+        let small = parse_adapters(SMALL).unwrap();
+        assert_eq!(
+            difference_distribution(&small, config),
+            Some(vec![0, 7, 0, 5])
+        );
+        let large = parse_adapters(LARGE).unwrap();
+        assert_eq!(
+            difference_distribution(&large, config),
+            Some(vec![0, 22, 0, 10])
+        );
+        assert_eq!(difference_distribution(&[1, 5], config), None);
+    }
+
+    #[test]
+    fn count_distinct_arrangements() {
+        let config = AdapterConfig::default();
+
+        let small = parse_adapters(SMALL).unwrap();
+        assert_eq!(count_arrangements(&small, config).to_string(), "8");
+        let large = parse_adapters(LARGE).unwrap();
+        assert_eq!(count_arrangements(&large, config).to_string(), "19208");
+    }
+
+    #[test]
+    fn count_arrangements_beyond_u64() {
+        let adapters: Vec<u64> = (1..=120).collect();
+        let mut tribonacci: Vec<u128> = vec![1, 1, 2];
+        while tribonacci.len() <= adapters.len() {
+            let n = tribonacci.len();
+            tribonacci.push(tribonacci[n - 1] + tribonacci[n - 2] + tribonacci[n - 3]);
+        }
+        let expected = tribonacci[adapters.len()];
+
+        assert!(expected > u64::MAX as u128);
+        assert_eq!(
+            count_arrangements(&adapters, AdapterConfig::default()).to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    fn configure_step_size_and_device_offset() {
+        let config = AdapterConfig {
+            max_step: 4,
+            device_offset: 4,
+        };
+
+        assert_eq!(
+            difference_distribution(&[1, 5], config),
+            Some(vec![0, 1, 0, 0, 2])
+        );
+        assert_eq!(count_arrangements(&[1, 2, 3, 4], config).to_string(), "8");
+    }
+
+    #[test]
+    fn list_adapters_that_can_be_removed() {
+        let small = parse_adapters(SMALL).unwrap();
+
+        assert_eq!(
+            removable_adapters(&small, AdapterConfig::default()),
+            vec![5, 6, 11]
+        );
+    }
+}
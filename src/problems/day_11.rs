@@ -0,0 +1,251 @@
+use super::grid::{Grid, DIRECTIONS};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Seat {
+    Floor,
+    Empty,
+    Occupied,
+}
+
+impl Seat {
+    fn parse(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Seat::Floor),
+            'L' => Some(Seat::Empty),
+            '#' => Some(Seat::Occupied),
+            _ => None,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Seat::Floor => '.',
+            Seat::Empty => 'L',
+            Seat::Occupied => '#',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Visibility {
+    Adjacent,
+    LineOfSight,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SeatingRules {
+    visibility: Visibility,
+    occupy_at_most: usize,
+    leave_at_least: usize,
+}
+
+impl SeatingRules {
+    fn adjacent() -> Self {
+        SeatingRules {
+            visibility: Visibility::Adjacent,
+            occupy_at_most: 0,
+            leave_at_least: 4,
+        }
+    }
+
+    fn line_of_sight() -> Self {
+        SeatingRules {
+            visibility: Visibility::LineOfSight,
+            occupy_at_most: 0,
+            leave_at_least: 5,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Settlement {
+    Stable { generation: usize, occupied: usize },
+    Oscillation { start: usize, period: usize },
+    GenerationLimitExceeded,
+}
+
+fn parse_layout(input: &str) -> Option<Grid<Seat>> {
+    Grid::parse(input, Seat::parse)
+}
+
+fn render(layout: &Grid<Seat>) -> String {
+    layout.render(|seat| seat.to_char())
+}
+
+fn count_occupied(layout: &Grid<Seat>) -> usize {
+    layout
+        .cells()
+        .iter()
+        .filter(|seat| **seat == Seat::Occupied)
+        .count()
+}
+
+// The seats each seat looks at never change, so they are found once up front.
+fn visible_seats(layout: &Grid<Seat>, visibility: Visibility) -> Vec<Vec<usize>> {
+    layout
+        .positions()
+        .map(|position| {
+            DIRECTIONS
+                .iter()
+                .filter_map(|direction| {
+                    let mut current = layout.offset(position, *direction)?;
+                    while visibility == Visibility::LineOfSight
+                        && layout.get(current.0, current.1) == Some(&Seat::Floor)
+                    {
+                        current = layout.offset(current, *direction)?;
+                    }
+                    Some(layout.index_of(current.0, current.1))
+                })
+                .filter(|index| layout.cells()[*index] != Seat::Floor)
+                .collect()
+        })
+        .collect()
+}
+
+fn step(layout: &Grid<Seat>, visible: &[Vec<usize>], rules: SeatingRules) -> Grid<Seat> {
+    let cells = layout.cells();
+    let next = cells
+        .iter()
+        .zip(visible)
+        .map(|(seat, neighbours)| {
+            let occupied = neighbours
+                .iter()
+                .filter(|index| cells[**index] == Seat::Occupied)
+                .count();
+            match seat {
+                Seat::Empty if occupied <= rules.occupy_at_most => Seat::Occupied,
+                Seat::Occupied if occupied >= rules.leave_at_least => Seat::Empty,
+                seat => *seat,
+            }
+        })
+        .collect();
+    layout.with_cells(next)
+}
+
+fn frames(layout: &Grid<Seat>, rules: SeatingRules) -> impl Iterator<Item = Grid<Seat>> {
+    let visible = visible_seats(layout, rules.visibility);
+    std::iter::successors(Some(layout.clone()), move |current| {
+        Some(step(current, &visible, rules))
+    })
+}
+
+fn settle(layout: &Grid<Seat>, rules: SeatingRules, max_generations: usize) -> Settlement {
+    let mut seen: HashMap<Grid<Seat>, usize> = HashMap::new();
+
+    for (generation, frame) in frames(layout, rules).take(max_generations + 1).enumerate() {
+        if let Some(start) = seen.get(&frame) {
+            let period = generation - start;
+            return if period == 1 {
+                Settlement::Stable {
+                    generation: *start,
+                    occupied: count_occupied(&frame),
+                }
+            } else {
+                Settlement::Oscillation {
+                    start: *start,
+                    period,
+                }
+            };
+        }
+        seen.insert(frame, generation);
+    }
+    Settlement::GenerationLimitExceeded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LAYOUT: &str = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    #[test]
+    fn render_frames_of_the_adjacent_rule() {
+        let layout = parse_layout(LAYOUT).unwrap();
+        let frames: Vec<String> = frames(&layout, SeatingRules::adjacent())
+            .take(3)
+            .map(|frame| render(&frame))
+            .collect();
+
+        assert_eq!(render(&layout), format!("{}\n", LAYOUT));
+        assert_eq!(
+            frames[2],
+            "#.LL.L#.##
+#LLLLLL.L#
+L.L.L..L..
+#LLL.LL.L#
+#.LL.LL.LL
+#.LLLL#.##
+..L.L.....
+#LLLLLLLL#
+#.LLLLLL.L
+#.#LLLL.##
+"
+        );
+    }
+
+    #[test]
+    fn settle_with_adjacent_rule() {
+        let layout = parse_layout(LAYOUT).unwrap();
+
+        assert_eq!(
+            settle(&layout, SeatingRules::adjacent(), 100),
+            Settlement::Stable {
+                generation: 5,
+                occupied: 37
+            }
+        );
+    }
+
+    #[test]
+    fn settle_with_line_of_sight_rule() {
+        let layout = parse_layout(LAYOUT).unwrap();
+
+        assert_eq!(
+            settle(&layout, SeatingRules::line_of_sight(), 100),
+            Settlement::Stable {
+                generation: 6,
+                occupied: 26
+            }
+        );
+    }
+
+    #[test]
+    fn line_of_sight_skips_floor() {
+        let layout = parse_layout(".......#.\n...#.....\n.#.......\n.........\n..#L....#\n....#....\n.........\n#........\n...#.....").unwrap();
+        let visible = visible_seats(&layout, Visibility::LineOfSight);
+
+        assert_eq!(visible[layout.index_of(4, 3)].len(), 8);
+    }
+
+    #[test]
+    fn detect_oscillation_and_generation_limit() {
+        let layout = parse_layout("LL").unwrap();
+        let restless = SeatingRules {
+            visibility: Visibility::Adjacent,
+            occupy_at_most: 0,
+            leave_at_least: 1,
+        };
+
+        assert_eq!(
+            settle(&layout, restless, 10),
+            Settlement::Oscillation {
+                start: 0,
+                period: 2
+            }
+        );
+        assert_eq!(
+            settle(&parse_layout(LAYOUT).unwrap(), SeatingRules::adjacent(), 3),
+            Settlement::GenerationLimitExceeded
+        );
+    }
+}
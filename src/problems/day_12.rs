@@ -0,0 +1,201 @@
+#[derive(Debug, PartialEq)]
+enum NavigationError {
+    UnknownAction(String),
+    InvalidValue(String),
+    UnsupportedRotation(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Point {
+    east: i64,
+    north: i64,
+}
+
+impl Point {
+    const ORIGIN: Point = Point { east: 0, north: 0 };
+
+    fn new(east: i64, north: i64) -> Self {
+        Point { east, north }
+    }
+
+    fn translated(self, by: Point, times: i64) -> Self {
+        Point::new(self.east + by.east * times, self.north + by.north * times)
+    }
+
+    fn rotated_clockwise(self, quarter_turns: u8) -> Self {
+        (0..quarter_turns).fold(self, |point, _| Point::new(point.north, -point.east))
+    }
+
+    fn manhattan_distance(self) -> i64 {
+        self.east.abs() + self.north.abs()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    Move(Point),
+    Turn { clockwise_quarters: u8 },
+    Forward(i64),
+}
+
+impl Command {
+    fn parse(command: &str) -> Result<Self, NavigationError> {
+        let command = command.trim();
+        let action = command
+            .chars()
+            .next()
+            .ok_or_else(|| NavigationError::UnknownAction(command.to_string()))?;
+        let value = command[action.len_utf8()..]
+            .parse::<i64>()
+            .map_err(|_| NavigationError::InvalidValue(command.to_string()))?;
+
+        match action {
+            'N' => Ok(Command::Move(Point::new(0, value))),
+            'S' => Ok(Command::Move(Point::new(0, -value))),
+            'E' => Ok(Command::Move(Point::new(value, 0))),
+            'W' => Ok(Command::Move(Point::new(-value, 0))),
+            'R' => Command::turn(value),
+            'L' => Command::turn(-value),
+            'F' => Ok(Command::Forward(value)),
+            _ => Err(NavigationError::UnknownAction(command.to_string())),
+        }
+    }
+
+    fn turn(degrees: i64) -> Result<Self, NavigationError> {
+        if degrees % 90 != 0 {
+            return Err(NavigationError::UnsupportedRotation(degrees));
+        }
+        Ok(Command::Turn {
+            clockwise_quarters: (degrees / 90).rem_euclid(4) as u8,
+        })
+    }
+}
+
+fn parse_commands(input: &str) -> Result<Vec<Command>, NavigationError> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(Command::parse)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Heading,
+    Waypoint,
+}
+
+// Both modes steer by a vector: the unit heading of the ship, or the waypoint
+// relative to it. They only differ in what a N/S/E/W command moves.
+fn navigate(commands: &[Command], mode: Mode) -> Vec<Point> {
+    let mut ship = Point::ORIGIN;
+    let mut vector = match mode {
+        Mode::Heading => Point::new(1, 0),
+        Mode::Waypoint => Point::new(10, 1),
+    };
+    let mut path = vec![ship];
+
+    for command in commands {
+        match (*command, mode) {
+            (Command::Move(by), Mode::Heading) => ship = ship.translated(by, 1),
+            (Command::Move(by), Mode::Waypoint) => vector = vector.translated(by, 1),
+            (Command::Turn { clockwise_quarters }, _) => {
+                vector = vector.rotated_clockwise(clockwise_quarters)
+            }
+            (Command::Forward(times), _) => ship = ship.translated(vector, times),
+        }
+        path.push(ship);
+    }
+    path
+}
+
+fn distance_travelled(commands: &[Command], mode: Mode) -> i64 {
+    navigate(commands, mode)
+        .last()
+        .map(|ship| ship.manhattan_distance())
+        .unwrap_or(0)
+}
+
+fn bounding_box(path: &[Point]) -> Option<(Point, Point)> {
+    let first = *path.first()?;
+    Some(path.iter().fold((first, first), |(low, high), point| {
+        (
+            Point::new(low.east.min(point.east), low.north.min(point.north)),
+            Point::new(high.east.max(point.east), high.north.max(point.north)),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const COMMANDS: &str = "F10\nN3\nF7\nR90\nF11";
+
+    #[test]
+    fn parse_navigation_commands() {
+        assert_eq!(Command::parse("W5"), Ok(Command::Move(Point::new(-5, 0))));
+        assert_eq!(
+            Command::parse("L90"),
+            Ok(Command::Turn {
+                clockwise_quarters: 3
+            })
+        );
+        assert_eq!(
+            Command::parse("R45"),
+            Err(NavigationError::UnsupportedRotation(45))
+        );
+        assert_eq!(
+            Command::parse("X1"),
+            Err(NavigationError::UnknownAction("X1".to_string()))
+        );
+        assert_eq!(
+            Command::parse("F"),
+            Err(NavigationError::InvalidValue("F".to_string()))
+        );
+    }
+
+    #[test]
+    fn navigate_by_heading() {
+        let commands = parse_commands(COMMANDS).unwrap();
+        let path = navigate(&commands, Mode::Heading);
+
+        assert_eq!(distance_travelled(&commands, Mode::Heading), 25);
+        assert_eq!(
+            path,
+            vec![
+                Point::new(0, 0),
+                Point::new(10, 0),
+                Point::new(10, 3),
+                Point::new(17, 3),
+                Point::new(17, 3),
+                Point::new(17, -8),
+            ]
+        );
+        assert_eq!(
+            bounding_box(&path),
+            Some((Point::new(0, -8), Point::new(17, 3)))
+        );
+    }
+
+    #[test]
+    fn navigate_by_waypoint() {
+        let commands = parse_commands(COMMANDS).unwrap();
+
+        assert_eq!(distance_travelled(&commands, Mode::Waypoint), 286);
+        assert_eq!(
+            navigate(&commands, Mode::Waypoint).last(),
+            Some(&Point::new(214, -72))
+        );
+    }
+
+    #[test]
+    fn opposite_turns_cancel_out() {
+        let commands = parse_commands("L270\nR90\nF1\nL180\nR180\nF1").unwrap();
+
+        assert_eq!(
+            navigate(&commands, Mode::Heading).last(),
+            Some(&Point::new(-2, 0))
+        );
+    }
+}
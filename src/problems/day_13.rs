@@ -0,0 +1,114 @@
+use super::number_theory::{chinese_remainder, lcm};
+
+#[derive(Debug, PartialEq)]
+struct Notes {
+    earliest: i128,
+    buses: Vec<Option<i128>>,
+}
+
+impl Notes {
+    fn parse(input: &str) -> Option<Self> {
+        let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+        let earliest = lines.next()?.parse::<i128>().ok()?;
+        let buses = lines
+            .next()?
+            .split(',')
+            .map(|bus| match bus {
+                "x" => Some(None),
+                bus => bus.parse::<i128>().ok().filter(|id| *id > 0).map(Some),
+            })
+            .collect::<Option<Vec<Option<i128>>>>()?;
+        Some(Notes { earliest, buses })
+    }
+
+    fn running_buses(&self) -> impl Iterator<Item = (i128, i128)> + '_ {
+        self.buses
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, bus)| bus.map(|id| (offset as i128, id)))
+    }
+}
+
+// Returns the bus to take and how long to wait for it.
+fn earliest_departure(notes: &Notes) -> Option<(i128, i128)> {
+    notes
+        .running_buses()
+        .map(|(_, id)| (id, (-notes.earliest).rem_euclid(id)))
+        .min_by_key(|(_, wait)| *wait)
+}
+
+// Bus `id` leaving `offset` minutes after t means t = -offset (mod id).
+fn aligned_departures(notes: &Notes) -> Option<i128> {
+    let congruences: Vec<(i128, i128)> = notes
+        .running_buses()
+        .map(|(offset, id)| (-offset, id))
+        .collect();
+    chinese_remainder(&congruences).map(|(timestamp, _)| timestamp)
+}
+
+// Steps through candidates by the period of the buses aligned so far. Once a
+// bus has been tried for a whole period of the combined step without matching
+// it never will.
+fn aligned_departures_by_sieve(notes: &Notes) -> Option<i128> {
+    let mut timestamp = 0;
+    let mut step = 1;
+    for (offset, id) in notes.running_buses() {
+        let period = lcm(step, id);
+        let limit = timestamp + period;
+        while (timestamp + offset) % id != 0 {
+            timestamp += step;
+            if timestamp >= limit {
+                return None;
+            }
+        }
+        step = period;
+    }
+    Some(timestamp)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_earliest_bus() {
+        let notes = Notes::parse("939\n7,13,x,x,59,x,31,19").unwrap();
+
+        assert_eq!(earliest_departure(&notes), Some((59, 5)));
+    }
+
+    #[test]
+    fn align_departures_with_offsets() {
+        for (buses, expected) in &[
+            ("7,13,x,x,59,x,31,19", 1068781),
+            ("17,x,13,19", 3417),
+            ("67,7,59,61", 754018),
+            ("67,x,7,59,61", 779210),
+            ("67,7,x,59,61", 1261476),
+            ("1789,37,47,1889", 1202161486),
+        ] {
+            let notes = Notes::parse(&format!("0\n{}", buses)).unwrap();
+
+            assert_eq!(aligned_departures(&notes), Some(*expected));
+            assert_eq!(aligned_departures_by_sieve(&notes), Some(*expected));
+        }
+    }
+
+    #[test]
+    fn report_no_solution_for_conflicting_non_coprime_buses() {
+        let conflicting = Notes::parse("0\n4,6").unwrap();
+        let compatible = Notes::parse("0\n4,x,6").unwrap();
+
+        assert_eq!(aligned_departures(&conflicting), None);
+        assert_eq!(aligned_departures_by_sieve(&conflicting), None);
+        assert_eq!(aligned_departures(&compatible), Some(4));
+        assert_eq!(aligned_departures_by_sieve(&compatible), Some(4));
+    }
+
+    #[test]
+    fn reject_malformed_notes() {
+        assert_eq!(Notes::parse("939"), None);
+        assert_eq!(Notes::parse("939\n7,y"), None);
+        assert_eq!(Notes::parse("939\n7,0"), None);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+const MASK_BITS: usize = 36;
+
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    MalformedLine(usize),
+    MalformedMask(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Mask {
+    ones: u64,
+    zeros: u64,
+    floating: u64,
+}
+
+impl Mask {
+    fn parse(mask: &str) -> Option<Self> {
+        if mask.len() != MASK_BITS {
+            return None;
+        }
+        mask.chars().try_fold(
+            Mask {
+                ones: 0,
+                zeros: 0,
+                floating: 0,
+            },
+            |mask, bit| {
+                let shifted = Mask {
+                    ones: mask.ones << 1,
+                    zeros: mask.zeros << 1,
+                    floating: mask.floating << 1,
+                };
+                match bit {
+                    '1' => Some(Mask {
+                        ones: shifted.ones | 1,
+                        ..shifted
+                    }),
+                    '0' => Some(Mask {
+                        zeros: shifted.zeros | 1,
+                        ..shifted
+                    }),
+                    'X' => Some(Mask {
+                        floating: shifted.floating | 1,
+                        ..shifted
+                    }),
+                    _ => None,
+                }
+            },
+        )
+    }
+
+    fn apply_to_value(&self, value: u64) -> u64 {
+        (value | self.ones) & !self.zeros
+    }
+
+    fn floating_address_count(&self) -> u64 {
+        1 << self.floating.count_ones()
+    }
+
+    // Walks every subset of the floating bits, from all set down to none.
+    fn addresses(&self, address: u64) -> impl Iterator<Item = u64> {
+        let base = (address | self.ones) & !self.floating;
+        let floating = self.floating;
+        let mut subset = Some(floating);
+        std::iter::from_fn(move || {
+            let current = subset?;
+            subset = current.checked_sub(1).map(|next| next & floating);
+            Some(base | current)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instruction {
+    SetMask(Mask),
+    Write { address: u64, value: u64 },
+}
+
+fn parse_instruction(line_number: usize, line: &str) -> Result<Instruction, ParseError> {
+    let malformed = || ParseError::MalformedLine(line_number);
+    let mut parts = line.split(" = ");
+    let target = parts.next().ok_or_else(malformed)?;
+    let argument = parts.next().ok_or_else(malformed)?;
+
+    if target == "mask" {
+        return Mask::parse(argument)
+            .map(Instruction::SetMask)
+            .ok_or(ParseError::MalformedMask(line_number));
+    }
+    let address = target
+        .strip_prefix("mem[")
+        .and_then(|target| target.strip_suffix(']'))
+        .and_then(|address| address.parse::<u64>().ok())
+        .ok_or_else(malformed)?;
+    let value = argument.parse::<u64>().map_err(|_| malformed())?;
+    Ok(Instruction::Write { address, value })
+}
+
+fn parse_program(input: &str) -> Result<Vec<(usize, Instruction)>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line_number, line)| {
+            parse_instruction(line_number, line).map(|instruction| (line_number, instruction))
+        })
+        .collect()
+}
+
+// A mask full of floating bits spans billions of addresses, so writes above
+// the limit stop the program instead of being expanded.
+#[derive(Debug, PartialEq)]
+enum DockingError {
+    TooManyFloatingAddresses { line: usize, addresses: u64 },
+}
+
+#[derive(Debug, Default)]
+struct Docking {
+    memory: HashMap<u64, u64>,
+}
+
+impl Docking {
+    fn sum(&self) -> u64 {
+        self.memory.values().sum()
+    }
+}
+
+fn run_version_1(program: &[(usize, Instruction)]) -> Docking {
+    let mut docking = Docking::default();
+    let mut mask = None;
+    for (_, instruction) in program {
+        match instruction {
+            Instruction::SetMask(new_mask) => mask = Some(*new_mask),
+            Instruction::Write { address, value } => {
+                let value = mask.map_or(*value, |mask| mask.apply_to_value(*value));
+                docking.memory.insert(*address, value);
+            }
+        }
+    }
+    docking
+}
+
+fn run_version_2(
+    program: &[(usize, Instruction)],
+    max_addresses: u64,
+) -> Result<Docking, DockingError> {
+    let mut docking = Docking::default();
+    let mut mask = None;
+    for (line, instruction) in program {
+        match instruction {
+            Instruction::SetMask(new_mask) => mask = Some(*new_mask),
+            Instruction::Write { address, value } => match mask {
+                Some(mask) => {
+                    let addresses = mask.floating_address_count();
+                    if addresses > max_addresses {
+                        return Err(DockingError::TooManyFloatingAddresses {
+                            line: *line,
+                            addresses,
+                        });
+                    }
+                    mask.addresses(*address).for_each(|address| {
+                        docking.memory.insert(address, *value);
+                    });
+                }
+                None => {
+                    docking.memory.insert(*address, *value);
+                }
+            },
+        }
+    }
+    Ok(docking)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mask_values_in_version_1() {
+        let program = parse_program(
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 11
+mem[7] = 101
+mem[8] = 0",
+        )
+        .unwrap();
+        let docking = run_version_1(&program);
+
+        assert_eq!(docking.memory.get(&8), Some(&64));
+        assert_eq!(docking.sum(), 165);
+    }
+
+    #[test]
+    fn expand_floating_addresses_in_version_2() {
+        let program = parse_program(
+            "mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1",
+        )
+        .unwrap();
+        let docking = run_version_2(&program, 1 << 10).unwrap();
+
+        let mut addresses: Vec<u64> = Mask::parse("000000000000000000000000000000X1001X")
+            .unwrap()
+            .addresses(42)
+            .collect();
+        addresses.sort_unstable();
+        assert_eq!(addresses, vec![26, 27, 58, 59]);
+        assert_eq!(docking.sum(), 208);
+    }
+
+    #[test]
+    fn reject_masks_with_too_many_floating_bits() {
+        let program = parse_program(
+            "mask = 0000000000000000000000000000000000XX
+mem[0] = 1
+mask = 00000000000000000000000000000000XXXX
+mem[0] = 1",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run_version_2(&program, 8).err(),
+            Some(DockingError::TooManyFloatingAddresses {
+                line: 4,
+                addresses: 16
+            })
+        );
+        assert_eq!(run_version_2(&program, 16).unwrap().sum(), 16);
+    }
+
+    #[test]
+    fn reject_writes_through_a_fully_floating_mask() {
+        let program = parse_program(
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+mem[0] = 1",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run_version_2(&program, 1 << 20).err(),
+            Some(DockingError::TooManyFloatingAddresses {
+                line: 2,
+                addresses: 1 << 36
+            })
+        );
+    }
+
+    #[test]
+    fn report_malformed_lines() {
+        assert_eq!(
+            parse_program("mask = 01X\nmem[1] = 2"),
+            Err(ParseError::MalformedMask(1))
+        );
+        assert_eq!(
+            parse_program("mem[1] = 2\nmem[x] = 2"),
+            Err(ParseError::MalformedLine(2))
+        );
+    }
+}
@@ -0,0 +1,108 @@
+const NEVER_SPOKEN: u32 = 0;
+
+fn parse_starting_numbers(input: &str) -> Option<Vec<u32>> {
+    input
+        .trim()
+        .split(',')
+        .map(|number| number.trim().parse::<u32>().ok())
+        .collect()
+}
+
+// Every spoken number is an age, and ages are smaller than the turn count, so
+// a flat table indexed by number holds the turn each one was last spoken.
+pub struct Spoken {
+    starting: Vec<u32>,
+    last_seen: Vec<u32>,
+    turn: u32,
+    last: Option<u32>,
+}
+
+impl Spoken {
+    pub fn new(starting: &[u32]) -> Self {
+        Spoken::with_capacity(starting, 0)
+    }
+
+    pub fn with_capacity(starting: &[u32], turns: usize) -> Self {
+        let largest_start = starting.iter().max().map_or(0, |n| *n as usize + 1);
+        Spoken {
+            starting: starting.to_vec(),
+            last_seen: vec![NEVER_SPOKEN; turns.max(largest_start)],
+            turn: 0,
+            last: None,
+        }
+    }
+
+    pub fn table_bytes(&self) -> usize {
+        self.last_seen.len() * std::mem::size_of::<u32>()
+    }
+}
+
+impl Iterator for Spoken {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.turn += 1;
+        let previous_turn = self.turn - 1;
+        let spoken = match self.starting.get(previous_turn as usize) {
+            Some(number) => *number,
+            None => {
+                let last = self.last? as usize;
+                match self.last_seen[last] {
+                    NEVER_SPOKEN => 0,
+                    seen => previous_turn - seen,
+                }
+            }
+        };
+
+        if let Some(last) = self.last {
+            self.last_seen[last as usize] = previous_turn;
+        }
+        if spoken as usize >= self.last_seen.len() {
+            self.last_seen.resize(spoken as usize + 1, NEVER_SPOKEN);
+        }
+        self.last = Some(spoken);
+        Some(spoken)
+    }
+}
+
+fn spoken_at(starting: &[u32], turn: usize) -> Option<u32> {
+    Spoken::with_capacity(starting, turn).nth(turn.checked_sub(1)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn iterate_over_the_sequence() {
+        let starting = parse_starting_numbers("0,3,6").unwrap();
+        let spoken: Vec<u32> = Spoken::new(&starting).take(10).collect();
+
+        assert_eq!(spoken, vec![0, 3, 6, 0, 3, 3, 1, 0, 4, 0]);
+    }
+
+    #[test]
+    fn find_2020th_number_spoken() {
+        for (starting, expected) in &[
+            ("0,3,6", 436),
+            ("1,3,2", 1),
+            ("2,1,3", 10),
+            ("1,2,3", 27),
+            ("2,3,1", 78),
+            ("3,2,1", 438),
+            ("3,1,2", 1836),
+        ] {
+            let starting = parse_starting_numbers(starting).unwrap();
+
+            assert_eq!(spoken_at(&starting, 2020), Some(*expected));
+        }
+        assert_eq!(spoken_at(&[0, 3, 6], 0), None);
+    }
+
+    #[test]
+    fn starting_numbers_may_repeat() {
+        let spoken: Vec<u32> = Spoken::new(&[1, 1]).take(5).collect();
+
+        assert_eq!(spoken, vec![1, 1, 1, 1, 1]);
+    }
+}
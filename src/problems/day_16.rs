@@ -0,0 +1,247 @@
+use super::validation::Rule;
+
+#[derive(Debug, PartialEq)]
+struct Field<'i> {
+    name: &'i str,
+    rule: Rule,
+}
+
+#[derive(Debug, PartialEq)]
+struct Notes<'i> {
+    fields: Vec<Field<'i>>,
+    your_ticket: Vec<usize>,
+    nearby_tickets: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum AssignmentError<'i> {
+    Contradiction,
+    Ambiguous {
+        position: usize,
+        candidates: Vec<&'i str>,
+    },
+}
+
+fn parse_ticket(line: &str) -> Option<Vec<usize>> {
+    line.trim()
+        .split(',')
+        .map(|value| value.parse::<usize>().ok())
+        .collect()
+}
+
+fn parse_tickets(section: &str, header: &str) -> Option<Vec<Vec<usize>>> {
+    let mut lines = section
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    if lines.next()? != header {
+        return None;
+    }
+    lines.map(parse_ticket).collect()
+}
+
+impl<'i> Notes<'i> {
+    fn parse(input: &'i str) -> Option<Self> {
+        let mut sections = input.trim().split("\n\n");
+        let fields = sections
+            .next()?
+            .lines()
+            .map(|line| {
+                let mut parts = line.trim().split(": ");
+                let name = parts.next()?;
+                let rule = Rule::parse(parts.next()?)?;
+                Some(Field { name, rule })
+            })
+            .collect::<Option<Vec<Field>>>()?;
+        let your_ticket = parse_tickets(sections.next()?, "your ticket:")?
+            .pop()
+            .filter(|ticket| ticket.len() == fields.len())?;
+        let nearby_tickets = parse_tickets(sections.next()?, "nearby tickets:")?;
+
+        Some(Notes {
+            fields,
+            your_ticket,
+            nearby_tickets,
+        })
+    }
+
+    fn matches_any_field(&self, value: usize) -> bool {
+        self.fields.iter().any(|field| field.rule.matches(value))
+    }
+
+    fn scanning_error_rate(&self) -> usize {
+        self.nearby_tickets
+            .iter()
+            .flatten()
+            .filter(|value| !self.matches_any_field(**value))
+            .sum()
+    }
+
+    fn valid_tickets(&self) -> impl Iterator<Item = &Vec<usize>> {
+        self.nearby_tickets.iter().filter(move |ticket| {
+            ticket.len() == self.fields.len()
+                && ticket.iter().all(|value| self.matches_any_field(*value))
+        })
+    }
+
+    // candidates[position][field] holds when every valid ticket agrees with
+    // the field's rule at that position.
+    fn candidates(&self) -> Vec<Vec<bool>> {
+        let tickets: Vec<&Vec<usize>> = self.valid_tickets().collect();
+        (0..self.fields.len())
+            .map(|position| {
+                self.fields
+                    .iter()
+                    .map(|field| {
+                        tickets
+                            .iter()
+                            .all(|ticket| field.rule.matches(ticket[position]))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn assign_fields(&self) -> Result<Vec<&'i str>, AssignmentError<'i>> {
+        let mut candidates = self.candidates();
+        let field_of_position =
+            perfect_matching(&candidates).ok_or(AssignmentError::Contradiction)?;
+
+        // The matching is the only one when no edge of it can be replaced.
+        for (position, field) in field_of_position.iter().enumerate() {
+            candidates[position][*field] = false;
+            if let Some(other) = perfect_matching(&candidates) {
+                let mut names = vec![self.fields[*field].name, self.fields[other[position]].name];
+                names.sort_unstable();
+                return Err(AssignmentError::Ambiguous {
+                    position,
+                    candidates: names,
+                });
+            }
+            candidates[position][*field] = true;
+        }
+        Ok(field_of_position
+            .into_iter()
+            .map(|field| self.fields[field].name)
+            .collect())
+    }
+
+    fn departure_product(&self) -> Result<usize, AssignmentError<'i>> {
+        Ok(self
+            .assign_fields()?
+            .iter()
+            .zip(&self.your_ticket)
+            .filter(|(name, _)| name.starts_with("departure"))
+            .map(|(_, value)| value)
+            .product())
+    }
+}
+
+// Kuhn's augmenting paths over positions; returns the field for each position.
+fn perfect_matching(candidates: &[Vec<bool>]) -> Option<Vec<usize>> {
+    fn augment(
+        position: usize,
+        candidates: &[Vec<bool>],
+        visited: &mut Vec<bool>,
+        position_of_field: &mut Vec<Option<usize>>,
+    ) -> bool {
+        for field in 0..candidates[position].len() {
+            if !candidates[position][field] || visited[field] {
+                continue;
+            }
+            visited[field] = true;
+            let free = match position_of_field[field] {
+                None => true,
+                Some(owner) => augment(owner, candidates, visited, position_of_field),
+            };
+            if free {
+                position_of_field[field] = Some(position);
+                return true;
+            }
+        }
+        false
+    }
+
+    let fields = candidates.first().map_or(0, |row| row.len());
+    let mut position_of_field = vec![None; fields];
+    for position in 0..candidates.len() {
+        let mut visited = vec![false; fields];
+        if !augment(position, candidates, &mut visited, &mut position_of_field) {
+            return None;
+        }
+    }
+
+    let mut field_of_position = vec![0; candidates.len()];
+    for (field, position) in position_of_field.into_iter().enumerate() {
+        field_of_position[position?] = field;
+    }
+    Some(field_of_position)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOTES: &str = "class: 1-3 or 5-7
+row: 6-11 or 33-44
+seat: 13-40 or 45-50
+
+your ticket:
+7,1,14
+
+nearby tickets:
+7,3,47
+40,4,50
+55,2,20
+38,6,12";
+
+    const ASSIGNABLE: &str = "class: 0-1 or 4-19
+row: 0-5 or 8-19
+seat: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9";
+
+    #[test]
+    fn compute_scanning_error_rate() {
+        let notes = Notes::parse(NOTES).unwrap();
+
+        assert_eq!(notes.scanning_error_rate(), 71);
+        assert_eq!(notes.valid_tickets().count(), 1);
+    }
+
+    #[test]
+    fn assign_fields_to_positions() {
+        let notes = Notes::parse(ASSIGNABLE).unwrap();
+
+        assert_eq!(notes.assign_fields(), Ok(vec!["row", "class", "seat"]));
+        assert_eq!(notes.departure_product(), Ok(1));
+    }
+
+    #[test]
+    fn report_ambiguous_assignment() {
+        let input = ASSIGNABLE.replace("5,14,9", "");
+        let notes = Notes::parse(&input).unwrap();
+
+        assert_eq!(
+            notes.assign_fields(),
+            Err(AssignmentError::Ambiguous {
+                position: 1,
+                candidates: vec!["class", "seat"]
+            })
+        );
+    }
+
+    #[test]
+    fn report_contradictory_assignment() {
+        let input = ASSIGNABLE.replace("row: 0-5 or 8-19", "row: 0-5");
+        let notes = Notes::parse(&input).unwrap();
+
+        assert_eq!(notes.assign_fields(), Err(AssignmentError::Contradiction));
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet};
+
+const PLANE: usize = 2;
+const EXTRA_DIMENSION_NAMES: [&str; 2] = ["z", "w"];
+
+// The starting slice lies at zero in every dimension beyond the plane, so the
+// pocket stays symmetric under flipping the sign of any extra coordinate or
+// swapping two of them. Only cells whose extra coordinates are non-negative
+// and sorted are stored.
+#[derive(Debug, Clone, PartialEq)]
+struct PocketDimension {
+    dimensions: usize,
+    active: HashSet<Vec<i32>>,
+}
+
+fn canonical(mut cell: Vec<i32>) -> Vec<i32> {
+    let extra = &mut cell[PLANE..];
+    extra
+        .iter_mut()
+        .for_each(|coordinate| *coordinate = coordinate.abs());
+    extra.sort_unstable();
+    cell
+}
+
+fn factorial(n: usize) -> usize {
+    (1..=n).product()
+}
+
+// How many cells of the full space a stored cell stands for.
+fn orbit_size(cell: &[i32]) -> usize {
+    let extra = &cell[PLANE..];
+    let mut repeats: HashMap<i32, usize> = HashMap::new();
+    extra
+        .iter()
+        .for_each(|coordinate| *repeats.entry(*coordinate).or_default() += 1);
+    let permutations =
+        factorial(extra.len()) / repeats.values().map(|n| factorial(*n)).product::<usize>();
+    let sign_flips = 1 << extra.iter().filter(|coordinate| **coordinate != 0).count();
+    permutations * sign_flips
+}
+
+fn neighbour_offsets(dimensions: usize) -> Vec<Vec<i32>> {
+    (0..dimensions)
+        .fold(vec![Vec::new()], |offsets, _| {
+            offsets
+                .into_iter()
+                .flat_map(|offset| {
+                    (-1..=1).map(move |step| {
+                        let mut offset = offset.clone();
+                        offset.push(step);
+                        offset
+                    })
+                })
+                .collect()
+        })
+        .into_iter()
+        .filter(|offset| offset.iter().any(|step| *step != 0))
+        .collect()
+}
+
+impl PocketDimension {
+    fn parse(input: &str, dimensions: usize) -> Option<Self> {
+        if dimensions < PLANE {
+            return None;
+        }
+        let mut active = HashSet::new();
+        for (y, line) in input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .enumerate()
+        {
+            for (x, cube) in line.chars().enumerate() {
+                match cube {
+                    '#' => {
+                        let mut cell = vec![0; dimensions];
+                        cell[0] = x as i32;
+                        cell[1] = y as i32;
+                        active.insert(cell);
+                    }
+                    '.' => {}
+                    _ => return None,
+                }
+            }
+        }
+        Some(PocketDimension { dimensions, active })
+    }
+
+    fn is_active(&self, cell: Vec<i32>) -> bool {
+        self.active.contains(&canonical(cell))
+    }
+
+    fn step(&self) -> Self {
+        let offsets = neighbour_offsets(self.dimensions);
+        let shifted = |cell: &[i32], offset: &[i32]| -> Vec<i32> {
+            cell.iter().zip(offset).map(|(c, o)| c + o).collect()
+        };
+
+        let candidates: HashSet<Vec<i32>> = self
+            .active
+            .iter()
+            .flat_map(|cell| {
+                offsets
+                    .iter()
+                    .map(move |offset| canonical(shifted(cell, offset)))
+            })
+            .chain(self.active.iter().cloned())
+            .collect();
+
+        let active = candidates
+            .into_iter()
+            .filter(|cell| {
+                let neighbours = offsets
+                    .iter()
+                    .filter(|offset| self.is_active(shifted(cell, offset)))
+                    .count();
+                neighbours == 3 || (neighbours == 2 && self.active.contains(cell))
+            })
+            .collect();
+        PocketDimension {
+            dimensions: self.dimensions,
+            active,
+        }
+    }
+
+    fn run(&self, cycles: usize) -> Self {
+        (0..cycles).fold(self.clone(), |pocket, _| pocket.step())
+    }
+
+    fn count_active(&self) -> usize {
+        self.active.iter().map(|cell| orbit_size(cell)).sum()
+    }
+
+    fn bounds(&self, dimension: usize) -> (i32, i32) {
+        let coordinates = self.active.iter().map(|cell| cell[dimension]);
+        (
+            coordinates.clone().min().unwrap_or(0),
+            coordinates.max().unwrap_or(0),
+        )
+    }
+
+    fn render_slice(&self, extra: &[i32]) -> String {
+        let (min_x, max_x) = self.bounds(0);
+        let (min_y, max_y) = self.bounds(1);
+        let mut slice = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let mut cell = vec![x, y];
+                cell.extend_from_slice(extra);
+                slice.push(if self.is_active(cell) { '#' } else { '.' });
+            }
+            slice.push('\n');
+        }
+        slice
+    }
+
+    fn slice_label(extra: &[i32]) -> String {
+        extra
+            .iter()
+            .enumerate()
+            .map(
+                |(index, coordinate)| match EXTRA_DIMENSION_NAMES.get(index) {
+                    Some(name) => format!("{}={}", name, coordinate),
+                    None => format!("d{}={}", index + PLANE + 1, coordinate),
+                },
+            )
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    // Prints every slice holding an active cube, ordered like the puzzle text
+    // with the first extra dimension varying fastest.
+    fn render(&self) -> String {
+        let reach = self
+            .active
+            .iter()
+            .flat_map(|cell| cell[PLANE..].iter().copied())
+            .max()
+            .unwrap_or(0);
+        let extra_slices = (PLANE..self.dimensions).fold(vec![Vec::new()], |slices, _| {
+            (-reach..=reach)
+                .flat_map(|coordinate| {
+                    slices.iter().map(move |slice: &Vec<i32>| {
+                        let mut slice = slice.clone();
+                        slice.push(coordinate);
+                        slice
+                    })
+                })
+                .collect()
+        });
+
+        extra_slices
+            .into_iter()
+            .map(|extra| {
+                (
+                    PocketDimension::slice_label(&extra),
+                    self.render_slice(&extra),
+                )
+            })
+            .filter(|(_, slice)| slice.contains('#'))
+            .map(|(label, slice)| format!("{}\n{}", label, slice))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INITIAL: &str = ".#.\n..#\n###";
+
+    #[test]
+    fn boot_in_three_dimensions() {
+        let pocket = PocketDimension::parse(INITIAL, 3).unwrap();
+
+        assert_eq!(pocket.count_active(), 5);
+        assert_eq!(pocket.run(1).count_active(), 11);
+        assert_eq!(pocket.run(6).count_active(), 112);
+    }
+
+    #[test]
+    fn boot_in_four_dimensions() {
+        let pocket = PocketDimension::parse(INITIAL, 4).unwrap();
+
+        assert_eq!(pocket.run(1).count_active(), 29);
+        assert_eq!(pocket.run(6).count_active(), 848);
+    }
+
+    #[test]
+    fn symmetric_storage_counts_every_mirrored_cell() {
+        assert_eq!(orbit_size(&[0, 0, 0, 0]), 1);
+        assert_eq!(orbit_size(&[0, 0, 0, 1]), 4);
+        assert_eq!(orbit_size(&[0, 0, 1, 1]), 4);
+        assert_eq!(orbit_size(&[0, 0, 1, 2]), 8);
+        assert_eq!(canonical(vec![5, 6, -2, 1]), vec![5, 6, 1, 2]);
+        assert_eq!(neighbour_offsets(4).len(), 80);
+    }
+
+    #[test]
+    fn print_slices_like_the_puzzle() {
+        let pocket = PocketDimension::parse(INITIAL, 3).unwrap().run(1);
+
+        assert_eq!(
+            pocket.render(),
+            "z=-1
+#..
+..#
+.#.
+
+z=0
+#.#
+.##
+.#.
+
+z=1
+#..
+..#
+.#.
+"
+        );
+        let pocket = PocketDimension::parse(INITIAL, 4).unwrap().run(1);
+        assert!(pocket
+            .render()
+            .starts_with("z=-1, w=-1\n#..\n..#\n.#.\n\nz=0, w=-1\n"));
+    }
+
+    #[test]
+    fn reject_unknown_cubes_and_too_few_dimensions() {
+        assert_eq!(PocketDimension::parse(".#x", 3), None);
+        assert_eq!(PocketDimension::parse(INITIAL, 1), None);
+    }
+}
@@ -1,20 +1,54 @@
-pub fn find_tree_on_slop(input: &Vec<Vec<u8>>, down_by: usize, shift_right_by: usize) -> usize {
-    let mut result: Vec<u8> = Vec::with_capacity(input.len());
+use super::grid::Grid;
+
+pub fn parse_tree_map(input: &str) -> Option<Grid<u8>> {
+    Grid::parse(input, |c| match c {
+        '#' => Some(1),
+        '.' => Some(0),
+        _ => None,
+    })
+}
+
+pub fn find_tree_on_slop(input: &Grid<u8>, down_by: usize, shift_right_by: usize) -> usize {
+    let mut result: Vec<u8> = Vec::with_capacity(input.height());
 
     let mut cursor = 0;
-    for row_index in (0..input.len()).step_by(down_by) {
-        let row = &input[row_index];
-        let index = cursor % row.len();
-        result.push(row[index]);
+    for row_index in (0..input.height()).step_by(down_by) {
+        let index = cursor % input.width();
+        result.push(*input.get(row_index, index).unwrap());
         cursor += shift_right_by;
     }
     result.into_iter().filter(|x| *x == 1).count()
 }
 
-pub fn find_product_of_trees(input: &Vec<Vec<u8>>) -> usize {
+pub fn find_product_of_trees(input: &Grid<u8>) -> usize {
     let mut prod = 1;
     for pair in vec![[1, 1], [3, 1], [5, 1], [7, 1], [1, 2]] {
         prod = prod * find_tree_on_slop(&input, pair[1], pair[0]);
     }
     prod
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAP: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    #[test]
+    fn count_trees_on_a_slope() {
+        let map = parse_tree_map(MAP).unwrap();
+
+        assert_eq!(find_tree_on_slop(&map, 1, 3), 7);
+        assert_eq!(find_product_of_trees(&map), 336);
+    }
+}
@@ -1,3 +1,4 @@
+use super::validation::ValueRange;
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -28,8 +29,8 @@ impl Year {
         input.parse::<usize>().ok().map(|value| Year { value })
     }
 
-    fn is_between_include_edges(&self, min: usize, max: usize) -> bool {
-        (min..max).contains(&self.value)
+    fn is_within(&self, range: ValueRange) -> bool {
+        range.contains(self.value)
     }
 }
 
@@ -57,8 +58,12 @@ impl<'i> Height<'i> {
     }
 
     fn is_valid(&self) -> bool {
-        let expected_range = if self.unit == "cm" { 150..194 } else { 59..77 };
-        expected_range.contains(&self.value)
+        let expected_range = if self.unit == "cm" {
+            ValueRange::new(150, 193)
+        } else {
+            ValueRange::new(59, 76)
+        };
+        expected_range.contains(self.value)
     }
 }
 
@@ -130,9 +135,9 @@ impl<'i> PasswordDoc<'i> {
     }
 
     fn is_valid(&self) -> bool {
-        self.birth_year.is_between_include_edges(1920, 2003)
-            && self.issue_year.is_between_include_edges(2010, 2021)
-            && self.expiration_year.is_between_include_edges(2020, 2031)
+        self.birth_year.is_within(ValueRange::new(1920, 2002))
+            && self.issue_year.is_within(ValueRange::new(2010, 2020))
+            && self.expiration_year.is_within(ValueRange::new(2020, 2030))
             && self.height.is_valid()
             && self.eye_color.is_valid_eye_color()
             && self.hair_color.is_valid_hair_color()
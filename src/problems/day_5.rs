@@ -1,25 +1,45 @@
 use std::ops::Range;
 
-fn get_get_id_for_code(code: &str, lower_bond: usize, upper_bond: usize) -> usize {
-    code.chars()
-        .fold((lower_bond, upper_bond), |range, c| {
-            if c == 'F' || c == 'L' {
-                (range.0, mid_of(range))
-            } else {
-                (mid_of(range), range.1)
-            }
-        })
-        .0
+const ROW_BITS: usize = 7;
+const COLUMN_BITS: usize = 3;
+const ROWS: usize = 1 << ROW_BITS;
+const COLUMNS: usize = 1 << COLUMN_BITS;
+
+fn code_to_binary(code: &[u8], zero: u8, one: u8) -> Option<usize> {
+    code.iter().try_fold(0, |id, c| match *c {
+        c if c == zero => Some(id << 1),
+        c if c == one => Some((id << 1) | 1),
+        _ => None,
+    })
 }
 
-fn get_seat_id(ticket_number: &str) -> usize {
-    let row = get_get_id_for_code(&ticket_number[..7], 0, 127);
-    let column = get_get_id_for_code(&ticket_number[7..10], 0, 7);
-    (row * 8) + column
+fn decode_seat(ticket_number: &str) -> Option<(usize, usize)> {
+    let seat_id = get_seat_id(ticket_number)?;
+    Some((seat_id >> COLUMN_BITS, seat_id & (COLUMNS - 1)))
 }
 
-fn mid_of(tuple: (usize, usize)) -> usize {
-    (tuple.0 + tuple.1 + 1) / 2
+fn get_seat_id(ticket_number: &str) -> Option<usize> {
+    let code = ticket_number.as_bytes();
+    if code.len() != ROW_BITS + COLUMN_BITS {
+        return None;
+    }
+    let row = code_to_binary(&code[..ROW_BITS], b'F', b'B')?;
+    let column = code_to_binary(&code[ROW_BITS..], b'L', b'R')?;
+    Some((row << COLUMN_BITS) | column)
+}
+
+fn encode_seat(row: usize, column: usize) -> Option<String> {
+    if row >= ROWS || column >= COLUMNS {
+        return None;
+    }
+
+    let row_code = (0..ROW_BITS)
+        .rev()
+        .map(|bit| if row >> bit & 1 == 1 { 'B' } else { 'F' });
+    let column_code = (0..COLUMN_BITS)
+        .rev()
+        .map(|bit| if column >> bit & 1 == 1 { 'R' } else { 'L' });
+    Some(row_code.chain(column_code).collect())
 }
 
 fn get_largest_seat_id(tickets: &str) -> usize {
@@ -29,30 +49,112 @@ fn get_largest_seat_id(tickets: &str) -> usize {
 fn get_all_seat_ids(tickets: &str) -> Vec<usize> {
     tickets
         .split_ascii_whitespace()
-        .map(|ticket| get_seat_id(ticket))
+        .filter_map(get_seat_id)
         .collect()
 }
 
 fn seat_ids_having_diff_2(tickets: &str) -> Option<usize> {
-    let ids = get_all_seat_ids(tickets);
-    ids.to_owned()
-        .into_iter()
-        .find(|e| ids.contains(&(e + 2)) && !ids.contains(&(e + 1)))
-        .map(|s| s + 1)
+    let present = occupancy_bitset(&get_all_seat_ids(tickets), &(0..ROWS * COLUMNS));
+    (1..present.len() - 1).find(|id| !present[*id] && present[id - 1] && present[id + 1])
+}
+
+fn occupancy_bitset(ids: &[usize], range: &Range<usize>) -> Vec<bool> {
+    let mut present = vec![false; range.len()];
+    ids.iter()
+        .filter(|id| range.contains(id))
+        .for_each(|id| present[id - range.start] = true);
+    present
+}
+
+fn missing_ids_in_range(ids: &[usize], range: Range<usize>) -> Vec<usize> {
+    let present = occupancy_bitset(ids, &range);
+    let start = range.start;
+    range.filter(|id| !present[id - start]).collect()
+}
+
+fn single_missing_id(ids: &[usize]) -> Option<usize> {
+    let min = *ids.iter().min()?;
+    let max = *ids.iter().max()?;
+    if max - min > ids.len() {
+        return None;
+    }
+
+    match missing_ids_in_range(ids, min..max + 1).as_slice() {
+        [missing] => Some(*missing),
+        _ => None,
+    }
 }
 
-fn find_element_having_given_diff_with(
-    elements: &[usize],
-    element: usize,
-    diff: usize,
-) -> Option<&usize> {
-    elements.into_iter().find(|e| {
-        if element > **e {
-            element - **e == diff
-        } else {
-            **e - element == diff
+#[derive(Debug, PartialEq)]
+struct InvalidPass<'t>(&'t str);
+
+#[derive(Debug)]
+struct SeatMap<'t> {
+    passes: Vec<Vec<&'t str>>,
+}
+
+impl<'t> SeatMap<'t> {
+    fn parse(tickets: &'t str) -> Result<Self, InvalidPass<'t>> {
+        let mut passes = vec![Vec::new(); ROWS * COLUMNS];
+        for ticket in tickets.split_ascii_whitespace() {
+            let seat_id = get_seat_id(ticket).ok_or(InvalidPass(ticket))?;
+            passes[seat_id].push(ticket);
         }
-    })
+        Ok(SeatMap { passes })
+    }
+
+    fn is_occupied(&self, seat_id: usize) -> bool {
+        !self.passes[seat_id].is_empty()
+    }
+
+    fn gaps(&self) -> Vec<usize> {
+        let first = (0..self.passes.len()).find(|id| self.is_occupied(*id));
+        let last = (0..self.passes.len())
+            .rev()
+            .find(|id| self.is_occupied(*id));
+
+        match (first, last) {
+            (Some(first), Some(last)) => {
+                (first..last).filter(|id| !self.is_occupied(*id)).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn duplicate_passes(&self) -> Vec<&'t str> {
+        self.passes
+            .iter()
+            .filter(|passes| passes.len() > 1)
+            .map(|passes| passes[0])
+            .collect()
+    }
+
+    fn row_occupancy(&self) -> Vec<usize> {
+        self.passes
+            .chunks(COLUMNS)
+            .map(|row| row.iter().filter(|passes| !passes.is_empty()).count())
+            .collect()
+    }
+
+    fn render(&self) -> String {
+        self.passes
+            .chunks(COLUMNS)
+            .enumerate()
+            .map(|(row, seats)| {
+                let marks: Vec<char> = seats
+                    .iter()
+                    .map(|passes| if passes.is_empty() { '.' } else { '#' })
+                    .collect();
+                let (left, right) = marks.split_at(COLUMNS / 2);
+                format!(
+                    "{:>3} {} {}\n",
+                    row,
+                    left.iter().collect::<String>(),
+                    right.iter().collect::<String>()
+                )
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -61,17 +163,48 @@ mod test {
 
     #[test]
     fn get_row_number_given_ticket() {
-        assert_eq!(get_get_id_for_code("FBFBBFF", 0, 127), 44);
+        assert_eq!(code_to_binary(b"FBFBBFF", b'F', b'B'), Some(44));
     }
 
     #[test]
     fn get_column_number_given_ticket() {
-        assert_eq!(get_get_id_for_code("RLR", 0, 7), 5);
+        assert_eq!(code_to_binary(b"RLR", b'L', b'R'), Some(5));
+    }
+
+    #[test]
+    fn decode_ticket_into_row_and_column() {
+        assert_eq!(decode_seat("FBFBBFFRLR"), Some((44, 5)));
+        assert_eq!(decode_seat("BFFFBBFRRR"), Some((70, 7)));
+        assert_eq!(decode_seat("BBFFBBFRLL"), Some((102, 4)));
+    }
+
+    #[test]
+    fn encode_row_and_column_into_ticket() {
+        assert_eq!(encode_seat(44, 5), Some("FBFBBFFRLR".to_string()));
+        assert_eq!(encode_seat(0, 0), Some("FFFFFFFLLL".to_string()));
+        assert_eq!(encode_seat(127, 7), Some("BBBBBBBRRR".to_string()));
+    }
+
+    #[test]
+    fn encoding_and_decoding_every_seat_round_trips() {
+        for row in 0..ROWS {
+            for column in 0..COLUMNS {
+                let ticket = encode_seat(row, column).unwrap();
+                assert_eq!(decode_seat(&ticket), Some((row, column)));
+                assert_eq!(get_seat_id(&ticket), Some(row * COLUMNS + column));
+            }
+        }
+    }
+
+    #[test]
+    fn give_none_when_encoding_a_seat_outside_the_plane() {
+        assert_eq!(encode_seat(128, 0), None);
+        assert_eq!(encode_seat(0, 8), None);
     }
 
     #[test]
     fn get_seat_id_for_given_ticket() {
-        assert_eq!(get_seat_id("FBFBBFFRLR"), 357)
+        assert_eq!(get_seat_id("FBFBBFFRLR"), Some(357))
     }
 
     #[test]
@@ -1018,4 +1151,97 @@ BFFFBFBRLL
 FFFBBFFRLR";
         assert_eq!(seat_ids_having_diff_2(input), Some(607));
     }
+
+    #[test]
+    fn seat_map_finds_every_gap_between_first_and_last_occupied_seat() {
+        let tickets = "FFFFFFFLLL FFFFFFFLLR FFFFFFFLRR FFFFFFFRLL FFFFFFFRRR";
+        let seat_map = SeatMap::parse(tickets).unwrap();
+
+        assert_eq!(seat_map.gaps(), vec![2, 5, 6]);
+    }
+
+    #[test]
+    fn seat_map_has_no_gaps_when_empty() {
+        assert_eq!(SeatMap::parse("").unwrap().gaps(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn give_none_for_malformed_passes() {
+        assert_eq!(get_seat_id("FBFBBFF"), None);
+        assert_eq!(get_seat_id("FBFBBFFRLRR"), None);
+        assert_eq!(get_seat_id("FBFBXFFRLR"), None);
+        assert_eq!(get_seat_id("FBFBBFFRLB"), None);
+        assert_eq!(decode_seat("FBFéBFRLR"), None);
+    }
+
+    #[test]
+    fn seat_map_reports_invalid_passes() {
+        assert_eq!(
+            SeatMap::parse("FBFBBFFRLR FBF BFFFBBFRRR").unwrap_err(),
+            InvalidPass("FBF")
+        );
+    }
+
+    #[test]
+    fn seat_map_lists_duplicate_boarding_passes() {
+        let tickets = "FBFBBFFRLR BFFFBBFRRR FBFBBFFRLR BBFFBBFRLL FBFBBFFRLR BFFFBBFRRR";
+        let seat_map = SeatMap::parse(tickets).unwrap();
+
+        assert_eq!(
+            seat_map.duplicate_passes(),
+            vec!["FBFBBFFRLR", "BFFFBBFRRR"]
+        );
+    }
+
+    #[test]
+    fn seat_map_gives_occupancy_per_row() {
+        let tickets = "FFFFFFFLLL FFFFFFFRRR FFFFFFBLLL BBBBBBBRRR";
+        let occupancy = SeatMap::parse(tickets).unwrap().row_occupancy();
+
+        assert_eq!(occupancy.len(), ROWS);
+        assert_eq!(occupancy[0], 2);
+        assert_eq!(occupancy[1], 1);
+        assert_eq!(occupancy[127], 1);
+        assert_eq!(occupancy.iter().sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn seat_map_renders_plane_layout() {
+        let tickets = "FFFFFFFLLL FFFFFFFRRR FFFFFFBLRL";
+        let rendered = SeatMap::parse(tickets).unwrap().render();
+        let mut rows = rendered.lines();
+
+        assert_eq!(rows.next(), Some("  0 #... ...#"));
+        assert_eq!(rows.next(), Some("  1 ..#. ...."));
+        assert_eq!(rows.last(), Some("127 .... ...."));
+    }
+
+    #[test]
+    fn find_every_missing_id_within_a_range() {
+        let ids = vec![3, 9, 4, 7, 12, 5];
+
+        assert_eq!(missing_ids_in_range(&ids, 3..10), vec![6, 8]);
+        assert_eq!(missing_ids_in_range(&ids, 0..4), vec![0, 1, 2]);
+        assert_eq!(missing_ids_in_range(&ids, 4..6), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn find_single_missing_id_of_contiguous_ids() {
+        assert_eq!(single_missing_id(&[10, 8, 12, 11]), Some(9));
+        assert_eq!(single_missing_id(&[1, 3]), Some(2));
+    }
+
+    #[test]
+    fn give_none_when_ids_are_not_missing_exactly_one() {
+        assert_eq!(single_missing_id(&[]), None);
+        assert_eq!(single_missing_id(&[4, 5, 6]), None);
+        assert_eq!(single_missing_id(&[4, 7, 8]), None);
+    }
+
+    #[test]
+    fn ignore_repeated_ids_when_looking_for_the_missing_one() {
+        assert_eq!(single_missing_id(&[1, 3, 3, 5]), None);
+        assert_eq!(single_missing_id(&[1, 3, 3, 4]), Some(2));
+        assert_eq!(single_missing_id(&[7, 7]), None);
+    }
 }
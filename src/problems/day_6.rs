@@ -1,41 +1,450 @@
-use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::ops::{BitAnd, BitOr, BitXor, Not};
 
-fn count_unique_questions(questions: &str) -> usize {
-    let set: HashSet<char> = questions
-        .split_ascii_whitespace()
-        .map(|e| e.chars())
-        .flatten()
-        .collect();
-    set.len()
+#[derive(Debug, PartialEq)]
+pub enum SurveyError {
+    UnsupportedQuestion(char),
 }
 
-fn sum_of_of_unique_questions_per_group(questions_per_group: &str) -> usize {
-    questions_per_group
-        .split("\n\n")
-        .map(|question| count_unique_questions(question))
-        .sum()
+fn count_unique_questions(questions: &str) -> Result<usize, SurveyError> {
+    Expression::Union.count(questions)
 }
 
-fn question_count_to_which_we_all_yes_answer(questions: &str) -> usize {
-    let mut questions = questions.split_ascii_whitespace();
-    let first_set = questions.next().unwrap();
-    let remaining: Vec<&str> = questions.collect();
+pub fn sum_of_of_unique_questions_per_group(
+    questions_per_group: &str,
+) -> Result<usize, SurveyError> {
+    Expression::Union.sum_over_groups(questions_per_group)
+}
+
+fn question_count_to_which_we_all_yes_answer(questions: &str) -> Result<usize, SurveyError> {
+    Expression::Intersection.count(questions)
+}
+
+pub fn sum_of_questions_to_which_every_one_answered_yes_in_group(
+    question_per_group: &str,
+) -> Result<usize, SurveyError> {
+    Expression::Intersection.sum_over_groups(question_per_group)
+}
+
+trait AnswerBits:
+    Copy
+    + Debug
+    + PartialEq
+    + BitOr<Output = Self>
+    + BitAnd<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+{
+    const EMPTY: Self;
+    const QUESTIONS: u32;
+
+    fn question_index(question: char) -> Option<u32>;
+    fn question_at(index: u32) -> char;
+    fn single(index: u32) -> Self;
+    fn count_ones(self) -> u32;
+}
+
+macro_rules! answer_bits_ops {
+    () => {
+        const EMPTY: Self = 0;
+
+        fn single(index: u32) -> Self {
+            1 << index
+        }
+
+        fn count_ones(self) -> u32 {
+            self.count_ones()
+        }
+    };
+}
+
+impl AnswerBits for u32 {
+    answer_bits_ops!();
+    const QUESTIONS: u32 = 26;
+
+    fn question_index(question: char) -> Option<u32> {
+        match question {
+            'a'..='z' => Some(question as u32 - 'a' as u32),
+            _ => None,
+        }
+    }
+
+    fn question_at(index: u32) -> char {
+        (b'a' + index as u8) as char
+    }
+}
+
+impl AnswerBits for u64 {
+    answer_bits_ops!();
+    const QUESTIONS: u32 = 52;
+
+    fn question_index(question: char) -> Option<u32> {
+        match question {
+            'a'..='z' => Some(question as u32 - 'a' as u32),
+            'A'..='Z' => Some(question as u32 - 'A' as u32 + 26),
+            _ => None,
+        }
+    }
+
+    fn question_at(index: u32) -> char {
+        if index < 26 {
+            (b'a' + index as u8) as char
+        } else {
+            (b'A' + (index - 26) as u8) as char
+        }
+    }
+}
+
+impl AnswerBits for u128 {
+    answer_bits_ops!();
+    const QUESTIONS: u32 = 94;
+
+    fn question_index(question: char) -> Option<u32> {
+        match question {
+            '!'..='~' => Some(question as u32 - '!' as u32),
+            _ => None,
+        }
+    }
+
+    fn question_at(index: u32) -> char {
+        (b'!' + index as u8) as char
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Answers<B: AnswerBits = u32>(B);
+
+impl<B: AnswerBits> Answers<B> {
+    fn parse(answers: &str) -> Result<Self, SurveyError> {
+        answers
+            .chars()
+            .try_fold(B::EMPTY, |bits, question| {
+                B::question_index(question)
+                    .map(|index| bits | B::single(index))
+                    .ok_or(SurveyError::UnsupportedQuestion(question))
+            })
+            .map(Answers)
+    }
+
+    fn contains_index(&self, index: u32) -> bool {
+        self.0 & B::single(index) != B::EMPTY
+    }
+
+    fn contains(&self, question: char) -> bool {
+        B::question_index(question)
+            .map(|index| self.contains_index(index))
+            .unwrap_or(false)
+    }
+
+    fn count(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    fn questions(&self) -> Vec<char> {
+        (0..B::QUESTIONS)
+            .filter(|index| self.contains_index(*index))
+            .map(B::question_at)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Group<B: AnswerBits = u32> {
+    people: Vec<Answers<B>>,
+}
+
+impl<B: AnswerBits> Group<B> {
+    fn parse(answers: &str) -> Result<Self, SurveyError> {
+        Ok(Group {
+            people: answers
+                .split_ascii_whitespace()
+                .map(Answers::parse)
+                .collect::<Result<Vec<Answers<B>>, SurveyError>>()?,
+        })
+    }
+
+    fn union(&self) -> Answers<B> {
+        Answers(
+            self.people
+                .iter()
+                .fold(B::EMPTY, |acc, person| acc | person.0),
+        )
+    }
+
+    fn intersection(&self) -> Answers<B> {
+        Answers(
+            self.people
+                .iter()
+                .map(|person| person.0)
+                .reduce(|acc, person| acc & person)
+                .unwrap_or(B::EMPTY),
+        )
+    }
+
+    fn symmetric_difference(&self) -> Answers<B> {
+        Answers(
+            self.people
+                .iter()
+                .fold(B::EMPTY, |acc, person| acc ^ person.0),
+        )
+    }
+
+    fn answered_by_at_least(&self, k: usize) -> Answers<B> {
+        Answers(
+            (0..B::QUESTIONS)
+                .filter(|index| {
+                    self.people
+                        .iter()
+                        .filter(|person| person.contains_index(*index))
+                        .count()
+                        >= k
+                })
+                .fold(B::EMPTY, |bits, index| bits | B::single(index)),
+        )
+    }
 
-    first_set
-        .chars()
-        .filter(|c| all_contains(&remaining, c))
-        .count()
+    fn answered_by_exactly_one(&self) -> Answers<B> {
+        let (once, more_than_once) = self
+            .people
+            .iter()
+            .fold((B::EMPTY, B::EMPTY), |(once, more_than_once), person| {
+                (once | person.0, more_than_once | (once & person.0))
+            });
+        Answers(once & !more_than_once)
+    }
 }
 
-fn all_contains(vec: &Vec<&str>, character: &char) -> bool {
-    vec.into_iter().all(|v| v.contains(*character))
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Union,
+    Intersection,
+    SymmetricDifference,
+    AtLeast(usize),
+    ExactlyOne,
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Xor(Box<Expression>, Box<Expression>),
+    Except(Box<Expression>, Box<Expression>),
 }
 
-fn sum_of_questions_to_which_every_one_answered_yes_in_group(question_per_group: &str) -> usize {
-    question_per_group
-        .split("\n\n")
-        .map(|question| question_count_to_which_we_all_yes_answer(question))
-        .sum()
+impl Expression {
+    fn evaluate<B: AnswerBits>(&self, group: &Group<B>) -> Answers<B> {
+        match self {
+            Expression::Union => group.union(),
+            Expression::Intersection => group.intersection(),
+            Expression::SymmetricDifference => group.symmetric_difference(),
+            Expression::AtLeast(k) => group.answered_by_at_least(*k),
+            Expression::ExactlyOne => group.answered_by_exactly_one(),
+            Expression::And(left, right) => {
+                Answers(left.evaluate(group).0 & right.evaluate(group).0)
+            }
+            Expression::Or(left, right) => {
+                Answers(left.evaluate(group).0 | right.evaluate(group).0)
+            }
+            Expression::Xor(left, right) => {
+                Answers(left.evaluate(group).0 ^ right.evaluate(group).0)
+            }
+            Expression::Except(left, right) => {
+                Answers(left.evaluate(group).0 & !right.evaluate(group).0)
+            }
+        }
+    }
+
+    // Each group is held in the narrowest bit set that has room for every
+    // question it uses.
+    fn count(&self, questions: &str) -> Result<usize, SurveyError> {
+        let fits = |question_index: fn(char) -> Option<u32>| {
+            questions
+                .split_ascii_whitespace()
+                .flat_map(str::chars)
+                .all(|question| question_index(question).is_some())
+        };
+        if fits(<u32 as AnswerBits>::question_index) {
+            Ok(self.evaluate(&Group::<u32>::parse(questions)?).count())
+        } else if fits(<u64 as AnswerBits>::question_index) {
+            Ok(self.evaluate(&Group::<u64>::parse(questions)?).count())
+        } else {
+            Ok(self.evaluate(&Group::<u128>::parse(questions)?).count())
+        }
+    }
+
+    fn sum_over_groups(&self, questions_per_group: &str) -> Result<usize, SurveyError> {
+        questions_per_group
+            .split("\n\n")
+            .map(|group| self.count(group))
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct QuestionStatistics {
+    question: char,
+    groups: usize,
+    people: usize,
+    unanimous_groups: usize,
+}
+
+#[derive(Debug)]
+struct SurveyStatistics {
+    questions: Vec<QuestionStatistics>,
+    group_sizes: Vec<usize>,
+    people: Vec<Answers>,
+}
+
+impl SurveyStatistics {
+    fn parse(questions_per_group: &str) -> Result<Self, SurveyError> {
+        let groups: Vec<Group> = questions_per_group
+            .split("\n\n")
+            .map(Group::parse)
+            .collect::<Result<Vec<Group>, SurveyError>>()?
+            .into_iter()
+            .filter(|group| !group.people.is_empty())
+            .collect();
+
+        let questions = (0..u32::QUESTIONS)
+            .map(u32::question_at)
+            .map(|question| QuestionStatistics {
+                question,
+                groups: groups
+                    .iter()
+                    .filter(|group| group.union().contains(question))
+                    .count(),
+                people: groups
+                    .iter()
+                    .flat_map(|group| group.people.iter())
+                    .filter(|person| person.contains(question))
+                    .count(),
+                unanimous_groups: groups
+                    .iter()
+                    .filter(|group| group.intersection().contains(question))
+                    .count(),
+            })
+            .collect();
+
+        Ok(SurveyStatistics {
+            questions,
+            group_sizes: groups.iter().map(|group| group.people.len()).collect(),
+            people: groups.into_iter().flat_map(|group| group.people).collect(),
+        })
+    }
+
+    fn question(&self, question: char) -> Option<&QuestionStatistics> {
+        self.questions.iter().find(|q| q.question == question)
+    }
+
+    fn answered_questions(&self) -> impl Iterator<Item = &QuestionStatistics> {
+        self.questions.iter().filter(|q| q.people > 0)
+    }
+
+    fn most_agreed_upon(&self) -> Vec<char> {
+        let most = self.answered_questions().map(|q| q.unanimous_groups).max();
+        self.answered_questions()
+            .filter(|q| Some(q.unanimous_groups) == most)
+            .map(|q| q.question)
+            .collect()
+    }
+
+    fn least_agreed_upon(&self) -> Vec<char> {
+        let least = self.answered_questions().map(|q| q.unanimous_groups).min();
+        self.answered_questions()
+            .filter(|q| Some(q.unanimous_groups) == least)
+            .map(|q| q.question)
+            .collect()
+    }
+
+    fn correlation(&self, first: char, second: char) -> Option<f64> {
+        let total = self.people.len() as f64;
+        let count = |predicate: &dyn Fn(&Answers) -> bool| {
+            self.people.iter().filter(|p| predicate(p)).count() as f64
+        };
+        let first_yes = count(&|p| p.contains(first));
+        let second_yes = count(&|p| p.contains(second));
+        let both_yes = count(&|p| p.contains(first) && p.contains(second));
+
+        let variance = first_yes * (total - first_yes) * second_yes * (total - second_yes);
+        if variance == 0.0 {
+            return None;
+        }
+        Some((both_yes * total - first_yes * second_yes) / variance.sqrt())
+    }
+
+    fn correlation_matrix(&self) -> (Vec<char>, Vec<Vec<Option<f64>>>) {
+        let questions: Vec<char> = self.answered_questions().map(|q| q.question).collect();
+        let matrix = questions
+            .iter()
+            .map(|first| {
+                questions
+                    .iter()
+                    .map(|second| self.correlation(*first, *second))
+                    .collect()
+            })
+            .collect();
+        (questions, matrix)
+    }
+
+    fn to_table(&self) -> String {
+        let mut table = format!(
+            "{:<8} {:>6} {:>6} {:>9}\n",
+            "question", "groups", "people", "unanimous"
+        );
+        self.answered_questions().for_each(|q| {
+            table.push_str(&format!(
+                "{:<8} {:>6} {:>6} {:>9}\n",
+                q.question, q.groups, q.people, q.unanimous_groups
+            ))
+        });
+        let listed = |values: Vec<String>| values.join(" ");
+        let chars = |questions: Vec<char>| listed(questions.iter().map(char::to_string).collect());
+        table.push_str(&format!(
+            "\ngroup sizes: {}\nmost agreed upon: {}\nleast agreed upon: {}\n",
+            listed(self.group_sizes.iter().map(usize::to_string).collect()),
+            chars(self.most_agreed_upon()),
+            chars(self.least_agreed_upon())
+        ));
+        table
+    }
+
+    fn to_json(&self) -> String {
+        let questions: Vec<String> = self
+            .answered_questions()
+            .map(|q| {
+                format!(
+                    r#"{{"question":"{}","groups":{},"people":{},"unanimous_groups":{}}}"#,
+                    q.question, q.groups, q.people, q.unanimous_groups
+                )
+            })
+            .collect();
+        let group_sizes: Vec<String> = self.group_sizes.iter().map(|s| s.to_string()).collect();
+        let quoted = |questions: Vec<char>| -> String {
+            questions
+                .iter()
+                .map(|q| format!(r#""{}""#, q))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+
+        let (correlated, matrix) = self.correlation_matrix();
+        let rows: Vec<String> = matrix
+            .iter()
+            .map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| cell.map_or("null".to_string(), |c| format!("{:.3}", c)))
+                    .collect();
+                format!("[{}]", cells.join(","))
+            })
+            .collect();
+
+        format!(
+            r#"{{"questions":[{}],"group_sizes":[{}],"most_agreed_upon":[{}],"least_agreed_upon":[{}],"correlations":{{"questions":[{}],"matrix":[{}]}}}}"#,
+            questions.join(","),
+            group_sizes.join(","),
+            quoted(self.most_agreed_upon()),
+            quoted(self.least_agreed_upon()),
+            quoted(correlated),
+            rows.join(",")
+        )
+    }
 }
 
 #[cfg(test)]
@@ -59,26 +468,26 @@ a
 a
 
 b";
-        assert_eq!(sum_of_of_unique_questions_per_group(input), 11);
+        assert_eq!(sum_of_of_unique_questions_per_group(input), Ok(11));
     }
 
     #[test]
     fn count_sum_of_question_to_which_every_one_answered_yes_in_a_group() {
-        assert_eq!(question_count_to_which_we_all_yes_answer("abc"), 3);
+        assert_eq!(question_count_to_which_we_all_yes_answer("abc"), Ok(3));
         assert_eq!(
             question_count_to_which_we_all_yes_answer(
                 "a
 b
 c"
             ),
-            0
+            Ok(0)
         );
         assert_eq!(
             question_count_to_which_we_all_yes_answer(
                 "ab
 ac"
             ),
-            1
+            Ok(1)
         );assert_eq!(
             question_count_to_which_we_all_yes_answer(
                 "a
@@ -87,7 +496,214 @@ a
 a
 "
             ),
-            1
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn group_combines_answers_of_every_person() {
+        let group: Group = Group::parse(
+            "abc
+abd
+bce",
+        )
+        .unwrap();
+
+        assert_eq!(group.union().questions(), vec!['a', 'b', 'c', 'd', 'e']);
+        assert_eq!(group.intersection().questions(), vec!['b']);
+        assert_eq!(
+            group.symmetric_difference().questions(),
+            vec!['b', 'd', 'e']
+        );
+        assert_eq!(
+            group.answered_by_at_least(2).questions(),
+            vec!['a', 'b', 'c']
+        );
+        assert_eq!(group.answered_by_exactly_one().questions(), vec!['d', 'e']);
+    }
+
+    #[test]
+    fn empty_group_has_no_answers() {
+        let group: Group = Group::parse("").unwrap();
+
+        assert_eq!(group.union().count(), 0);
+        assert_eq!(group.intersection().count(), 0);
+        assert_eq!(group.answered_by_exactly_one().count(), 0);
+    }
+
+    #[test]
+    fn evaluate_expression_over_a_group() {
+        let group: Group = Group::parse(
+            "abc
+abd
+bce",
+        )
+        .unwrap();
+        let expression = Expression::Except(
+            Box::new(Expression::AtLeast(2)),
+            Box::new(Expression::Intersection),
+        );
+        assert_eq!(expression.evaluate(&group).questions(), vec!['a', 'c']);
+
+        let expression = Expression::Or(
+            Box::new(Expression::Intersection),
+            Box::new(Expression::ExactlyOne),
+        );
+        assert_eq!(expression.evaluate(&group).questions(), vec!['b', 'd', 'e']);
+    }
+
+    #[test]
+    fn sum_expression_over_every_group() {
+        let input = "abc
+
+a
+b
+c
+
+ab
+ac
+
+a
+a
+a
+a
+
+b";
+        assert_eq!(Expression::Union.sum_over_groups(input), Ok(11));
+        assert_eq!(Expression::Intersection.sum_over_groups(input), Ok(6));
+        assert_eq!(Expression::ExactlyOne.sum_over_groups(input), Ok(9));
+    }
+
+    #[test]
+    fn wider_alphabets_keep_questions_apart() {
+        let group = Group::<u64>::parse(
+            "aAbZ
+Ab",
+        )
+        .unwrap();
+        assert_eq!(group.union().questions(), vec!['a', 'b', 'A', 'Z']);
+        assert_eq!(group.intersection().questions(), vec!['b', 'A']);
+
+        let group = Group::<u128>::parse(
+            "a?1~
+?~z",
+        )
+        .unwrap();
+        assert_eq!(group.intersection().questions(), vec!['?', '~']);
+        assert_eq!(group.answered_by_exactly_one().count(), 3);
+    }
+
+    #[test]
+    fn widen_the_alphabet_when_questions_fall_outside_a_to_z() {
+        assert_eq!(count_unique_questions("ab\nAb"), Ok(3));
+        assert_eq!(count_unique_questions("a?\n?~z"), Ok(4));
+        assert_eq!(question_count_to_which_we_all_yes_answer("aB?z\n?B"), Ok(2));
+        assert_eq!(sum_of_of_unique_questions_per_group("abc\n\naZ\nZ!"), Ok(6));
+    }
+
+    #[test]
+    fn reject_questions_no_alphabet_can_hold() {
+        assert_eq!(
+            Answers::<u32>::parse("aB"),
+            Err(SurveyError::UnsupportedQuestion('B'))
+        );
+        assert_eq!(
+            count_unique_questions("ab\naé"),
+            Err(SurveyError::UnsupportedQuestion('é'))
+        );
+        assert_eq!(
+            SurveyStatistics::parse("ab\n\nA").err(),
+            Some(SurveyError::UnsupportedQuestion('A'))
+        );
+    }
+
+    #[test]
+    fn survey_statistics_per_question() {
+        let statistics = SurveyStatistics::parse(
+            "abc
+
+a
+b
+c
+
+ab
+ac
+
+a
+a
+a
+a
+
+b",
+        )
+        .unwrap();
+
+        assert_eq!(
+            statistics.question('a'),
+            Some(&QuestionStatistics {
+                question: 'a',
+                groups: 4,
+                people: 8,
+                unanimous_groups: 3
+            })
+        );
+        assert_eq!(statistics.question('c').map(|q| q.people), Some(3));
+        assert_eq!(statistics.question('z').map(|q| q.groups), Some(0));
+        assert_eq!(statistics.group_sizes, vec![1, 3, 2, 4, 1]);
+        assert_eq!(statistics.most_agreed_upon(), vec!['a']);
+        assert_eq!(statistics.least_agreed_upon(), vec!['c']);
+    }
+
+    #[test]
+    fn correlation_between_questions() {
+        let statistics = SurveyStatistics::parse(
+            "ab
+ab
+c
+c",
+        )
+        .unwrap();
+
+        assert_eq!(statistics.correlation('a', 'b'), Some(1.0));
+        assert_eq!(statistics.correlation('a', 'c'), Some(-1.0));
+        assert_eq!(statistics.correlation('a', 'z'), None);
+        assert_eq!(
+            statistics.correlation_matrix(),
+            (
+                vec!['a', 'b', 'c'],
+                vec![
+                    vec![Some(1.0), Some(1.0), Some(-1.0)],
+                    vec![Some(1.0), Some(1.0), Some(-1.0)],
+                    vec![Some(-1.0), Some(-1.0), Some(1.0)],
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn render_survey_statistics_as_table_and_json() {
+        let statistics = SurveyStatistics::parse(
+            "ab
+a
+
+b",
+        )
+        .unwrap();
+
+        assert_eq!(
+            statistics.to_table(),
+            "question groups people unanimous
+a             1      2         1
+b             2      2         1
+
+group sizes: 2 1
+most agreed upon: a b
+least agreed upon: a b
+"
+        );
+        assert_eq!(
+            statistics.to_json(),
+            r#"{"questions":[{"question":"a","groups":1,"people":2,"unanimous_groups":1},{"question":"b","groups":2,"people":2,"unanimous_groups":1}],"group_sizes":[2,1],"most_agreed_upon":["a","b"],"least_agreed_upon":["a","b"],"correlations":{"questions":["a","b"],"matrix":[[1.000,-0.500],[-0.500,1.000]]}}"#
         );
     }
 }
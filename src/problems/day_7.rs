@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, PartialEq)]
+enum LuggageError {
+    Cycle(String),
+}
+
+#[derive(Debug, PartialEq)]
+struct LuggageRules<'r> {
+    contents: HashMap<&'r str, Vec<(usize, &'r str)>>,
+}
+
+fn parse_content(content: &str) -> Option<(usize, &str)> {
+    let mut tokens = content.trim().splitn(2, ' ');
+    let count = tokens.next()?.parse::<usize>().ok()?;
+    let colour = tokens.next()?;
+    let colour = colour
+        .strip_suffix(" bags")
+        .or_else(|| colour.strip_suffix(" bag"))?;
+    Some((count, colour))
+}
+
+fn parse_rule(rule: &str) -> Option<(&str, Vec<(usize, &str)>)> {
+    let mut parts = rule.trim().trim_end_matches('.').split(" bags contain ");
+    let colour = parts.next()?;
+    let contents = parts.next()?;
+
+    if contents == "no other bags" {
+        return Some((colour, Vec::new()));
+    }
+    let contents = contents
+        .split(", ")
+        .map(parse_content)
+        .collect::<Option<Vec<(usize, &str)>>>()?;
+    Some((colour, contents))
+}
+
+impl<'r> LuggageRules<'r> {
+    fn parse(rules: &'r str) -> Option<Self> {
+        let contents = rules
+            .lines()
+            .filter(|rule| !rule.trim().is_empty())
+            .map(parse_rule)
+            .collect::<Option<HashMap<&str, Vec<(usize, &str)>>>>()?;
+        Some(LuggageRules { contents })
+    }
+
+    fn containers(&self) -> HashMap<&'r str, Vec<&'r str>> {
+        let mut containers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (outer, contents) in &self.contents {
+            for (_, inner) in contents {
+                containers.entry(*inner).or_default().push(*outer);
+            }
+        }
+        containers
+    }
+
+    fn count_colours_containing(&self, colour: &str) -> usize {
+        let containers = self.containers();
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut pending = vec![colour];
+
+        while let Some(current) = pending.pop() {
+            for outer in containers.get(current).into_iter().flatten() {
+                if seen.insert(outer) {
+                    pending.push(outer);
+                }
+            }
+        }
+        seen.remove(colour);
+        seen.len()
+    }
+
+    fn count_bags_inside(&self, colour: &str) -> Result<usize, LuggageError> {
+        let mut memo = HashMap::new();
+        let mut in_progress = HashSet::new();
+        self.count_bags_inside_memoized(colour, &mut memo, &mut in_progress)
+    }
+
+    fn count_bags_inside_memoized(
+        &self,
+        colour: &'r str,
+        memo: &mut HashMap<&'r str, usize>,
+        in_progress: &mut HashSet<&'r str>,
+    ) -> Result<usize, LuggageError> {
+        if let Some(count) = memo.get(colour) {
+            return Ok(*count);
+        }
+        if !in_progress.insert(colour) {
+            return Err(LuggageError::Cycle(colour.to_string()));
+        }
+
+        let mut count = 0;
+        for (quantity, inner) in self.contents.get(colour).into_iter().flatten() {
+            count += quantity * (1 + self.count_bags_inside_memoized(inner, memo, in_progress)?);
+        }
+
+        in_progress.remove(colour);
+        memo.insert(colour, count);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    #[test]
+    fn parse_a_rule_into_weighted_edges() {
+        assert_eq!(
+            parse_rule("light red bags contain 1 bright white bag, 2 muted yellow bags."),
+            Some(("light red", vec![(1, "bright white"), (2, "muted yellow")]))
+        );
+        assert_eq!(
+            parse_rule("faded blue bags contain no other bags."),
+            Some(("faded blue", vec![]))
+        );
+        assert_eq!(parse_rule("faded blue bags contain some bags."), None);
+    }
+
+    #[test]
+    fn count_colours_that_can_eventually_contain_shiny_gold() {
+        let rules = LuggageRules::parse(RULES).unwrap();
+
+        assert_eq!(rules.count_colours_containing("shiny gold"), 4);
+        assert_eq!(rules.count_colours_containing("light red"), 0);
+    }
+
+    #[test]
+    fn count_bags_inside_shiny_gold() {
+        let rules = LuggageRules::parse(RULES).unwrap();
+        assert_eq!(rules.count_bags_inside("shiny gold"), Ok(32));
+
+        let rules = LuggageRules::parse(
+            "shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain 2 dark yellow bags.
+dark yellow bags contain 2 dark green bags.
+dark green bags contain 2 dark blue bags.
+dark blue bags contain 2 dark violet bags.
+dark violet bags contain no other bags.",
+        )
+        .unwrap();
+        assert_eq!(rules.count_bags_inside("shiny gold"), Ok(126));
+    }
+
+    #[test]
+    fn report_cycles_instead_of_recursing_forever() {
+        let rules = LuggageRules::parse(
+            "shiny gold bags contain 1 dark red bag.
+dark red bags contain 2 pale teal bags.
+pale teal bags contain 1 shiny gold bag.",
+        )
+        .unwrap();
+
+        assert_eq!(
+            rules.count_bags_inside("shiny gold"),
+            Err(LuggageError::Cycle("shiny gold".to_string()))
+        );
+        assert_eq!(rules.count_colours_containing("shiny gold"), 2);
+    }
+}
@@ -0,0 +1,289 @@
+use super::{termination_tree, Instruction, Program};
+use std::convert::TryFrom;
+
+pub struct ControlFlowGraph {
+    instructions: Vec<Instruction>,
+    successors: Vec<isize>,
+}
+
+impl ControlFlowGraph {
+    // Programs with custom opcodes that branch have no fixed successors and
+    // so no graph.
+    pub fn build(program: &Program) -> Option<Self> {
+        Some(ControlFlowGraph {
+            instructions: program.instructions.clone(),
+            successors: program
+                .instructions
+                .iter()
+                .enumerate()
+                .map(|(address, instruction)| instruction.successor(address as isize))
+                .collect::<Option<Vec<isize>>>()?,
+        })
+    }
+
+    fn end(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn successor(&self, address: usize) -> Option<usize> {
+        usize::try_from(*self.successors.get(address)?)
+            .ok()
+            .filter(|successor| *successor <= self.end())
+    }
+
+    fn flipped_successor(&self, address: usize) -> Option<usize> {
+        let flipped = self.instructions.get(address)?.flipped()?;
+        usize::try_from(flipped.successor(address as isize)?)
+            .ok()
+            .filter(|successor| *successor <= self.end())
+    }
+
+    fn path_from_start(&self) -> Vec<usize> {
+        let mut on_path = vec![false; self.end()];
+        let mut path = Vec::new();
+        let mut address = Some(0);
+
+        while let Some(current) = address.filter(|a| *a < self.end() && !on_path[*a]) {
+            on_path[current] = true;
+            path.push(current);
+            address = self.successor(current);
+        }
+        path
+    }
+
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.end()];
+        self.path_from_start()
+            .into_iter()
+            .for_each(|address| reachable[address] = true);
+        reachable
+    }
+
+    pub fn unreachable_instructions(&self) -> Vec<usize> {
+        let reachable = self.reachable();
+        (0..self.end()).filter(|a| !reachable[*a]).collect()
+    }
+
+    pub fn instructions_on_cycles(&self) -> Vec<usize> {
+        const UNSEEN: u8 = 0;
+        const ON_STACK: u8 = 1;
+        const DONE: u8 = 2;
+
+        let mut status = vec![UNSEEN; self.end()];
+        let mut on_cycle = vec![false; self.end()];
+
+        for start in 0..self.end() {
+            let mut stack = Vec::new();
+            let mut address = Some(start);
+            while let Some(current) = address.filter(|a| *a < self.end()) {
+                match status[current] {
+                    UNSEEN => {
+                        status[current] = ON_STACK;
+                        stack.push(current);
+                        address = self.successor(current);
+                    }
+                    ON_STACK => {
+                        let cycle_start = stack.iter().position(|a| *a == current).unwrap();
+                        stack[cycle_start..]
+                            .iter()
+                            .for_each(|a| on_cycle[*a] = true);
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+            stack.into_iter().for_each(|a| status[a] = DONE);
+        }
+
+        (0..self.end()).filter(|a| on_cycle[*a]).collect()
+    }
+
+    pub fn terminating_patches(&self) -> Vec<usize> {
+        let (entered, exited) = termination_tree(&self.instructions);
+        let terminates = entered[0].is_some();
+        let reachable = self.reachable();
+        let passes_through = |from: usize, through: usize| match (entered[from], entered[through]) {
+            (Some(from_entered), Some(through_entered)) => {
+                through_entered <= from_entered && exited[from] <= exited[through]
+            }
+            _ => false,
+        };
+
+        (0..self.end())
+            .filter(|address| self.instructions[*address].flipped().is_some())
+            .filter(|address| {
+                if !reachable[*address] {
+                    return terminates;
+                }
+                self.flipped_successor(*address)
+                    .map(|successor| {
+                        entered[successor].is_some() && !passes_through(successor, *address)
+                    })
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let reachable = self.reachable();
+        let on_cycle = self.instructions_on_cycles();
+        let patches = self.terminating_patches();
+        let mut dot = String::from("digraph program {\n    end [shape=doublecircle];\n");
+
+        for (address, instruction) in self.instructions.iter().enumerate() {
+            let style = if !reachable[address] {
+                ", style=dotted"
+            } else if on_cycle.contains(&address) {
+                ", color=red"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "    n{} [label=\"{}: {}\"{}];\n",
+                address, address, instruction, style
+            ));
+        }
+        if (0..self.end()).any(|address| self.successor(address).is_none()) {
+            dot.push_str("    out_of_bounds [shape=octagon];\n");
+        }
+
+        let node = |successor: Option<usize>| match successor {
+            Some(successor) if successor == self.end() => "end".to_string(),
+            Some(successor) => format!("n{}", successor),
+            None => "out_of_bounds".to_string(),
+        };
+        for address in 0..self.end() {
+            dot.push_str(&format!(
+                "    n{} -> {};\n",
+                address,
+                node(self.successor(address))
+            ));
+        }
+        for address in patches {
+            dot.push_str(&format!(
+                "    n{} -> {} [style=dashed, color=green, label=\"flip\"];\n",
+                address,
+                node(self.flipped_successor(address))
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Machine, Outcome, BOOT_CODE};
+    use super::*;
+
+    fn boot_code() -> Program {
+        Program::parse(BOOT_CODE).unwrap()
+    }
+
+    #[test]
+    fn find_unreachable_instructions() {
+        let graph = ControlFlowGraph::build(&boot_code()).unwrap();
+
+        assert_eq!(graph.unreachable_instructions(), vec![5, 8]);
+    }
+
+    #[test]
+    fn find_instructions_on_cycles() {
+        let graph = ControlFlowGraph::build(&boot_code()).unwrap();
+        assert_eq!(graph.instructions_on_cycles(), vec![1, 2, 3, 4, 6, 7]);
+
+        let graph =
+            ControlFlowGraph::build(&Program::parse("jmp +2\njmp +0\njmp -2").unwrap()).unwrap();
+        assert_eq!(graph.instructions_on_cycles(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn give_none_for_successor_of_address_outside_the_program() {
+        let graph = ControlFlowGraph::build(&boot_code()).unwrap();
+
+        assert_eq!(graph.successor(8), Some(9));
+        assert_eq!(graph.successor(9), None);
+        assert_eq!(graph.flipped_successor(42), None);
+    }
+
+    #[test]
+    fn list_patches_that_make_program_terminate() {
+        let graph = ControlFlowGraph::build(&boot_code()).unwrap();
+
+        assert_eq!(graph.terminating_patches(), vec![7]);
+    }
+
+    #[test]
+    fn static_patches_agree_with_running_every_patch() {
+        let mut seed: u64 = 8;
+        let mut next = |bound: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % bound
+        };
+
+        for _ in 0..500 {
+            let size = next(8) + 1;
+            let instructions: Vec<Instruction> = (0..size)
+                .map(|_| {
+                    let argument = next(7) as i64 - 3;
+                    match next(3) {
+                        0 => Instruction::Acc(argument),
+                        1 => Instruction::Jmp(argument),
+                        _ => Instruction::Nop(argument),
+                    }
+                })
+                .collect();
+            let program = Program { instructions };
+
+            let expected: Vec<usize> = (0..program.len())
+                .filter(|address| {
+                    program
+                        .with_flipped(*address)
+                        .map(|patched| {
+                            matches!(Machine::new(&patched).run(), Outcome::Terminated { .. })
+                        })
+                        .unwrap_or(false)
+                })
+                .collect();
+
+            assert_eq!(
+                ControlFlowGraph::build(&program)
+                    .unwrap()
+                    .terminating_patches(),
+                expected,
+                "{:?}",
+                program
+            );
+        }
+    }
+
+    #[test]
+    fn export_graph_as_dot() {
+        let graph = ControlFlowGraph::build(
+            &Program::parse("nop +0\njmp -1\nacc +1\njmp +2\njmp +9").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph program {
+    end [shape=doublecircle];
+    n0 [label=\"0: nop +0\", color=red];
+    n1 [label=\"1: jmp -1\", color=red];
+    n2 [label=\"2: acc +1\", style=dotted];
+    n3 [label=\"3: jmp +2\", style=dotted];
+    n4 [label=\"4: jmp +9\", style=dotted];
+    out_of_bounds [shape=octagon];
+    n0 -> n1;
+    n1 -> n0;
+    n2 -> n3;
+    n3 -> end;
+    n4 -> out_of_bounds;
+    n1 -> n2 [style=dashed, color=green, label=\"flip\"];
+}
+"
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use super::{Instruction, Program};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Debug, PartialEq)]
+pub enum AssembleError {
+    UnknownOperation { line: usize, operation: String },
+    MissingArgument { line: usize },
+    InvalidArgument { line: usize, argument: String },
+    UndefinedSymbol { line: usize, symbol: String },
+    DuplicateSymbol { line: usize, symbol: String },
+}
+
+struct Statement<'s> {
+    line: usize,
+    operation: &'s str,
+    argument: Option<&'s str>,
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.split([';', '#']).next().unwrap_or_default()
+}
+
+fn is_identifier(token: &str) -> bool {
+    let mut chars = token.chars();
+    chars
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '_')
+        .unwrap_or(false)
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn define<'s, T>(
+    symbols: &mut HashMap<&'s str, T>,
+    name: &'s str,
+    value: T,
+    line: usize,
+) -> Result<(), AssembleError> {
+    if !is_identifier(name) {
+        return Err(AssembleError::InvalidArgument {
+            line,
+            argument: name.to_string(),
+        });
+    }
+    if symbols.insert(name, value).is_some() {
+        return Err(AssembleError::DuplicateSymbol {
+            line,
+            symbol: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub fn assemble(source: &str) -> Result<Program, AssembleError> {
+    let mut constants: HashMap<&str, i64> = HashMap::new();
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut statements = Vec::new();
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line = index + 1;
+        let mut text = strip_comment(raw_line).trim();
+
+        if let Some(definition) = text.strip_prefix("const ") {
+            let mut parts = definition.splitn(2, '=');
+            let name = parts.next().unwrap_or_default().trim();
+            let value = parts
+                .next()
+                .ok_or(AssembleError::MissingArgument { line })?
+                .trim();
+            let value = value
+                .parse::<i64>()
+                .map_err(|_| AssembleError::InvalidArgument {
+                    line,
+                    argument: value.to_string(),
+                })?;
+            define(&mut constants, name, value, line)?;
+            continue;
+        }
+
+        if let Some(colon) = text.find(':') {
+            let label = text[..colon].trim();
+            define(&mut labels, label, statements.len(), line)?;
+            text = text[colon + 1..].trim();
+        }
+
+        let mut tokens = text.split_ascii_whitespace();
+        if let Some(operation) = tokens.next() {
+            statements.push(Statement {
+                line,
+                operation,
+                argument: tokens.next(),
+            });
+        }
+        if let Some(extra) = tokens.next() {
+            return Err(AssembleError::InvalidArgument {
+                line,
+                argument: extra.to_string(),
+            });
+        }
+    }
+
+    let instructions = statements
+        .iter()
+        .enumerate()
+        .map(|(address, statement)| {
+            let line = statement.line;
+            let argument = statement
+                .argument
+                .ok_or(AssembleError::MissingArgument { line })?;
+            let resolve_value = || resolve_value(argument, &constants, line);
+            let resolve_offset = || match labels.get(argument) {
+                Some(target) => Ok(*target as i64 - address as i64),
+                None => resolve_value(),
+            };
+
+            match statement.operation {
+                "acc" => resolve_value().map(Instruction::Acc),
+                "jmp" => resolve_offset().map(Instruction::Jmp),
+                "nop" => resolve_offset().map(Instruction::Nop),
+                operation => Err(AssembleError::UnknownOperation {
+                    line,
+                    operation: operation.to_string(),
+                }),
+            }
+        })
+        .collect::<Result<Vec<Instruction>, AssembleError>>()?;
+
+    Ok(Program { instructions })
+}
+
+fn resolve_value(
+    argument: &str,
+    constants: &HashMap<&str, i64>,
+    line: usize,
+) -> Result<i64, AssembleError> {
+    if let Ok(value) = argument.parse::<i64>() {
+        return Ok(value);
+    }
+
+    let (negate, symbol) = match argument.as_bytes()[0] {
+        b'-' => (true, &argument[1..]),
+        b'+' => (false, &argument[1..]),
+        _ => (false, argument),
+    };
+    let invalid = || AssembleError::InvalidArgument {
+        line,
+        argument: argument.to_string(),
+    };
+    if !is_identifier(symbol) {
+        return Err(invalid());
+    }
+    let value = *constants
+        .get(symbol)
+        .ok_or_else(|| AssembleError::UndefinedSymbol {
+            line,
+            symbol: symbol.to_string(),
+        })?;
+    if negate {
+        value.checked_neg().ok_or_else(invalid)
+    } else {
+        Ok(value)
+    }
+}
+
+fn jump_target(address: usize, instruction: &Instruction, end: usize) -> Option<usize> {
+    let target = match *instruction {
+        Instruction::Jmp(offset) => address as i64 + offset,
+        Instruction::Nop(offset) if offset != 0 => address as i64 + offset,
+        _ => return None,
+    };
+    if (0..=end as i64).contains(&target) {
+        Some(target as usize)
+    } else {
+        None
+    }
+}
+
+pub fn disassemble(program: &Program) -> String {
+    let end = program.len();
+    let labels: BTreeMap<usize, String> = program
+        .instructions
+        .iter()
+        .enumerate()
+        .filter_map(|(address, instruction)| jump_target(address, instruction, end))
+        .collect::<BTreeSet<usize>>()
+        .into_iter()
+        .enumerate()
+        .map(|(index, target)| (target, format!("label_{}", index)))
+        .collect();
+
+    let mut source = String::new();
+    for (address, instruction) in program.instructions.iter().enumerate() {
+        if let Some(label) = labels.get(&address) {
+            source.push_str(&format!("{}:\n", label));
+        }
+        let line = match (jump_target(address, instruction, end), instruction) {
+            (Some(target), Instruction::Jmp(_)) => format!("jmp {}", labels[&target]),
+            (Some(target), Instruction::Nop(_)) => format!("nop {}", labels[&target]),
+            _ => instruction.to_string(),
+        };
+        source.push_str(&format!("    {}\n", line));
+    }
+    if let Some(label) = labels.get(&end) {
+        source.push_str(&format!("{}:\n", label));
+    }
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BOOT_CODE;
+    use super::*;
+
+    #[test]
+    fn assemble_labels_comments_and_constants() {
+        let source = "; accumulate STEP, skipping the jump back
+const STEP = 3
+const TIMES = -2
+
+start:
+    acc +STEP       ; accumulate
+    jmp check
+back: acc -TIMES
+check:
+    nop back        # flipping this would loop
+    jmp end
+    acc STEP
+end:";
+
+        assert_eq!(
+            assemble(source),
+            Ok(Program {
+                instructions: vec![
+                    Instruction::Acc(3),
+                    Instruction::Jmp(2),
+                    Instruction::Acc(2),
+                    Instruction::Nop(-1),
+                    Instruction::Jmp(2),
+                    Instruction::Acc(3),
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn assembled_program_matches_raw_offsets() {
+        let assembled = assemble(
+            "nop +0
+            acc +1
+            jmp skip
+            acc +3
+loop:       jmp -3
+            acc -99
+skip:       acc +1
+            jmp loop
+            acc +6",
+        )
+        .unwrap();
+        let raw = Program::parse(&BOOT_CODE.replace("jmp -4", "jmp -3")).unwrap();
+
+        assert_eq!(assembled, raw);
+    }
+
+    #[test]
+    fn report_assembly_errors_with_line_numbers() {
+        assert_eq!(
+            assemble("acc +1\njmp nowhere"),
+            Err(AssembleError::UndefinedSymbol {
+                line: 2,
+                symbol: "nowhere".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("a: nop +0\na: acc +1"),
+            Err(AssembleError::DuplicateSymbol {
+                line: 2,
+                symbol: "a".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("mul +2"),
+            Err(AssembleError::UnknownOperation {
+                line: 1,
+                operation: "mul".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("acc"),
+            Err(AssembleError::MissingArgument { line: 1 })
+        );
+        assert_eq!(
+            assemble("acc 1x"),
+            Err(AssembleError::InvalidArgument {
+                line: 1,
+                argument: "1x".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("nop +0\nacc +1 +2"),
+            Err(AssembleError::InvalidArgument {
+                line: 2,
+                argument: "+2".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("const MIN = -9223372036854775808\nacc MIN\nacc -MIN"),
+            Err(AssembleError::InvalidArgument {
+                line: 3,
+                argument: "-MIN".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn disassemble_recovers_labels_from_jump_targets() {
+        let program = Program::parse(&format!("{}\njmp +1", BOOT_CODE)).unwrap();
+
+        assert_eq!(
+            disassemble(&program),
+            "    nop +0
+label_0:
+    acc +1
+    jmp label_2
+label_1:
+    acc +3
+    jmp label_0
+    acc -99
+label_2:
+    acc +1
+    jmp label_1
+    acc +6
+    jmp label_3
+label_3:
+"
+        );
+    }
+
+    #[test]
+    fn disassembly_round_trips_through_assembler() {
+        let program = Program::parse(
+            "jmp +3
+        nop -1
+        acc +7
+        nop +2
+        jmp -4
+        jmp +40",
+        )
+        .unwrap();
+
+        assert_eq!(assemble(&disassemble(&program)), Ok(program));
+    }
+}
@@ -0,0 +1,403 @@
+use super::opcodes::Cpu;
+use super::{Instruction, Machine, OptMachineState, Outcome, Program};
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakpoint {
+    Address(usize),
+    AccumulatorEquals(i64),
+    AccumulatorAbove(i64),
+    AccumulatorBelow(i64),
+}
+
+impl Breakpoint {
+    fn parse(input: &str) -> Option<Self> {
+        let tokens: Vec<&str> = input.split_ascii_whitespace().collect();
+        match tokens.as_slice() {
+            [address] => address.parse().ok().map(Breakpoint::Address),
+            ["acc", "==", value] => value.parse().ok().map(Breakpoint::AccumulatorEquals),
+            ["acc", ">", value] => value.parse().ok().map(Breakpoint::AccumulatorAbove),
+            ["acc", "<", value] => value.parse().ok().map(Breakpoint::AccumulatorBelow),
+            _ => None,
+        }
+    }
+
+    fn is_hit_by(&self, state: &OptMachineState) -> bool {
+        match *self {
+            Breakpoint::Address(address) => state.instruction_pointer == address as isize,
+            Breakpoint::AccumulatorEquals(value) => state.accumulator == value,
+            Breakpoint::AccumulatorAbove(value) => state.accumulator > value,
+            Breakpoint::AccumulatorBelow(value) => state.accumulator < value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceEntry {
+    pub address: usize,
+    pub instruction: Instruction,
+    pub accumulator_before: i64,
+    pub accumulator_after: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stop {
+    Breakpoint(Breakpoint),
+    LoopDetected { ip: usize },
+    Halted(Outcome),
+}
+
+pub struct Debugger<'p> {
+    machine: Machine<'p>,
+    breakpoints: Vec<Breakpoint>,
+    visit_counts: Vec<usize>,
+    loop_reported: bool,
+}
+
+impl<'p> Debugger<'p> {
+    pub fn new(program: &'p Program) -> Self {
+        Debugger {
+            machine: Machine::new(program),
+            breakpoints: Vec::new(),
+            visit_counts: vec![0; program.len()],
+            loop_reported: false,
+        }
+    }
+
+    pub fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.machine = self.machine.with_step_limit(step_limit);
+        self
+    }
+
+    pub fn state(&self) -> OptMachineState {
+        self.machine.cpu.state
+    }
+
+    // The machine already records every executed address; accumulators are
+    // recovered by replaying those addresses from the initial state.
+    pub fn trace(&self) -> Vec<TraceEntry> {
+        let mut cpu = Cpu::default();
+        self.machine
+            .trace
+            .iter()
+            .map(|address| {
+                let instruction = self.machine.program.instructions[*address];
+                let accumulator_before = cpu.state.accumulator;
+                instruction.execute(&mut cpu);
+                TraceEntry {
+                    address: *address,
+                    instruction,
+                    accumulator_before,
+                    accumulator_after: cpu.state.accumulator,
+                }
+            })
+            .collect()
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    pub fn step(&mut self) -> Option<TraceEntry> {
+        let address = self.machine.cpu.state.instruction_pointer as usize;
+        let instruction = self.machine.current_instruction()?;
+        let accumulator_before = self.machine.cpu.state.accumulator;
+        let state = self.machine.step()?;
+
+        let entry = TraceEntry {
+            address,
+            instruction,
+            accumulator_before,
+            accumulator_after: state.accumulator,
+        };
+        self.visit_counts[address] += 1;
+        Some(entry)
+    }
+
+    // Only the first repeated instruction is reported as a loop; after that
+    // the machine runs on until a breakpoint, the end or the step limit.
+    pub fn resume(&mut self) -> Stop {
+        loop {
+            let step_limit = self.machine.step_limit;
+            if step_limit.map(|limit| self.machine.trace.len() >= limit) == Some(true) {
+                return Stop::Halted(Outcome::StepLimitExceeded);
+            }
+            if self.step().is_none() {
+                return Stop::Halted(self.halt_outcome());
+            }
+            let state = self.machine.cpu.state;
+            if let Some(breakpoint) = self.breakpoints.iter().find(|b| b.is_hit_by(&state)) {
+                return Stop::Breakpoint(*breakpoint);
+            }
+            if self.machine.current_instruction().is_none() {
+                return Stop::Halted(self.halt_outcome());
+            }
+            if !self.loop_reported && self.machine.has_visited(state.instruction_pointer) {
+                self.loop_reported = true;
+                return Stop::LoopDetected {
+                    ip: state.instruction_pointer as usize,
+                };
+            }
+        }
+    }
+
+    fn halt_outcome(&self) -> Outcome {
+        if let Some(stall) = &self.machine.stall {
+            stall.clone()
+        } else if self.machine.has_terminated() {
+            Outcome::Terminated {
+                acc: self.machine.cpu.state.accumulator,
+            }
+        } else {
+            Outcome::OutOfBounds {
+                ip: self.machine.cpu.state.instruction_pointer,
+            }
+        }
+    }
+
+    pub fn disassemble(&self) -> String {
+        self.machine
+            .program
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(address, instruction)| {
+                let current = self.machine.cpu.state.instruction_pointer == address as isize;
+                let breakpoint = self.breakpoints.contains(&Breakpoint::Address(address));
+                format!(
+                    "{}{}{:>4}  {:<10} visits {}\n",
+                    if current { '>' } else { ' ' },
+                    if breakpoint { '*' } else { ' ' },
+                    address,
+                    instruction.to_string(),
+                    self.visit_counts[address]
+                )
+            })
+            .collect()
+    }
+}
+
+const HELP: &str = "commands:
+  step [n]            execute n instructions (default 1)
+  continue            run until a breakpoint, the first repeated instruction or the end
+  break <address>     stop before executing the instruction at address
+  break acc <op> <n>  stop when the accumulator is ==, > or < n
+  clear               remove every breakpoint
+  state               print instruction pointer and accumulator
+  trace               print every executed instruction
+  list                print the program annotated with visit counts
+  quit                leave the debugger
+";
+
+const REPL_STEP_LIMIT: usize = 1_000_000;
+
+pub fn repl<R: BufRead, W: Write>(program: &Program, input: R, mut output: W) -> io::Result<()> {
+    let mut debugger = Debugger::new(program).with_step_limit(REPL_STEP_LIMIT);
+    write!(output, "(vm) ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        let (command, argument) = match line.trim().find(' ') {
+            Some(index) => (&line.trim()[..index], line.trim()[index..].trim()),
+            None => (line.trim(), ""),
+        };
+
+        match command {
+            "" => {}
+            "q" | "quit" => break,
+            "h" | "help" => write!(output, "{}", HELP)?,
+            "s" | "step" => {
+                let count = argument.parse().unwrap_or(1);
+                for _ in 0..count {
+                    match debugger.step() {
+                        Some(entry) => writeln!(
+                            output,
+                            "{:>4}  {:<10} acc {} -> {}",
+                            entry.address,
+                            entry.instruction.to_string(),
+                            entry.accumulator_before,
+                            entry.accumulator_after
+                        )?,
+                        None => {
+                            writeln!(output, "halted: {:?}", debugger.halt_outcome())?;
+                            break;
+                        }
+                    }
+                }
+            }
+            "c" | "continue" => writeln!(output, "{:?}", debugger.resume())?,
+            "b" | "break" => match Breakpoint::parse(argument) {
+                Some(breakpoint) => {
+                    debugger.add_breakpoint(breakpoint);
+                    writeln!(output, "breakpoint {:?}", breakpoint)?;
+                }
+                None => writeln!(output, "invalid breakpoint: {}", argument)?,
+            },
+            "clear" => debugger.clear_breakpoints(),
+            "state" => {
+                let state = debugger.state();
+                writeln!(
+                    output,
+                    "ip {} acc {}",
+                    state.instruction_pointer, state.accumulator
+                )?;
+            }
+            "trace" => {
+                for entry in debugger.trace() {
+                    writeln!(
+                        output,
+                        "{:>4}  {:<10} acc {} -> {}",
+                        entry.address,
+                        entry.instruction.to_string(),
+                        entry.accumulator_before,
+                        entry.accumulator_after
+                    )?;
+                }
+            }
+            "l" | "list" => write!(output, "{}", debugger.disassemble())?,
+            _ => writeln!(output, "unknown command: {} (try help)", command)?,
+        }
+        write!(output, "(vm) ")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BOOT_CODE;
+    use super::*;
+
+    fn boot_code() -> Program {
+        Program::parse(BOOT_CODE).unwrap()
+    }
+
+    #[test]
+    fn single_step_records_trace() {
+        let program = boot_code();
+        let mut debugger = Debugger::new(&program);
+
+        debugger.step();
+        let entry = debugger.step().unwrap();
+
+        assert_eq!(
+            entry,
+            TraceEntry {
+                address: 1,
+                instruction: Instruction::Acc(1),
+                accumulator_before: 0,
+                accumulator_after: 1
+            }
+        );
+        assert_eq!(debugger.trace().len(), 2);
+        assert_eq!(debugger.trace()[1], entry);
+        assert_eq!(debugger.state().instruction_pointer, 2);
+    }
+
+    #[test]
+    fn resume_stops_at_address_and_accumulator_breakpoints() {
+        let program = boot_code();
+        let mut debugger = Debugger::new(&program);
+        debugger.add_breakpoint(Breakpoint::Address(6));
+        debugger.add_breakpoint(Breakpoint::AccumulatorAbove(3));
+
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Address(6)));
+        assert_eq!(debugger.state().accumulator, 1);
+        assert_eq!(
+            debugger.resume(),
+            Stop::Breakpoint(Breakpoint::AccumulatorAbove(3))
+        );
+        assert_eq!(debugger.state().accumulator, 5);
+
+        debugger.clear_breakpoints();
+        assert_eq!(debugger.resume(), Stop::LoopDetected { ip: 1 });
+    }
+
+    #[test]
+    fn resume_after_a_reported_loop_runs_to_the_next_breakpoint() {
+        let program = boot_code();
+        let mut debugger = Debugger::new(&program).with_step_limit(100);
+
+        assert_eq!(debugger.resume(), Stop::LoopDetected { ip: 1 });
+        debugger.add_breakpoint(Breakpoint::Address(6));
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Address(6)));
+        debugger.clear_breakpoints();
+        debugger.add_breakpoint(Breakpoint::AccumulatorAbove(20));
+        assert_eq!(
+            debugger.resume(),
+            Stop::Breakpoint(Breakpoint::AccumulatorAbove(20))
+        );
+        assert_eq!(debugger.state().accumulator, 21);
+
+        debugger.clear_breakpoints();
+        assert_eq!(debugger.resume(), Stop::Halted(Outcome::StepLimitExceeded));
+        assert_eq!(debugger.trace().len(), 100);
+    }
+
+    #[test]
+    fn resume_reports_termination() {
+        let program = Program::parse("acc +2\nnop +0").unwrap();
+        let mut debugger = Debugger::new(&program);
+
+        assert_eq!(
+            debugger.resume(),
+            Stop::Halted(Outcome::Terminated { acc: 2 })
+        );
+        assert_eq!(debugger.step(), None);
+    }
+
+    #[test]
+    fn disassembly_is_annotated_with_visit_counts() {
+        let program = boot_code();
+        let mut debugger = Debugger::new(&program);
+        debugger.add_breakpoint(Breakpoint::Address(3));
+        debugger.resume();
+        debugger.step();
+        debugger.step();
+
+        let listing = debugger.disassemble();
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(lines[0], "     0  nop +0     visits 1");
+        assert_eq!(lines[1], ">    1  acc +1     visits 1");
+        assert_eq!(lines[3], " *   3  acc +3     visits 1");
+        assert_eq!(lines[5], "     5  acc -99    visits 0");
+    }
+
+    #[test]
+    fn parse_breakpoints() {
+        assert_eq!(Breakpoint::parse("12"), Some(Breakpoint::Address(12)));
+        assert_eq!(
+            Breakpoint::parse("acc == -4"),
+            Some(Breakpoint::AccumulatorEquals(-4))
+        );
+        assert_eq!(
+            Breakpoint::parse("acc < 7"),
+            Some(Breakpoint::AccumulatorBelow(7))
+        );
+        assert_eq!(Breakpoint::parse("acc ~ 7"), None);
+    }
+
+    #[test]
+    fn repl_executes_commands() {
+        let program = boot_code();
+        let input = "break 7\ncontinue\nstate\nstep 2\nquit\nstate\n";
+        let mut output = Vec::new();
+
+        repl(&program, input.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "(vm) breakpoint Address(7)
+(vm) Breakpoint(Address(7))
+(vm) ip 7 acc 2
+(vm)    7  jmp -4     acc 2 -> 2
+   3  acc +3     acc 2 -> 5
+(vm) "
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use super::{Instruction, Machine, OptMachineState, ParseError, Program};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Malformed(ParseError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub state: OptMachineState,
+    pub trace: Vec<usize>,
+}
+
+impl Snapshot {
+    pub fn to_text(&self) -> String {
+        let trace: Vec<String> = self.trace.iter().map(|ip| ip.to_string()).collect();
+        format!("{}\ntrace {}\n", self.state, trace.join(" "))
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let state = input.parse::<OptMachineState>()?;
+        let trace = input
+            .lines()
+            .find_map(|line| line.strip_prefix("trace"))
+            .ok_or_else(|| ParseError::MissingArgument("trace".to_string()))?
+            .split_ascii_whitespace()
+            .map(|ip| {
+                ip.parse::<usize>()
+                    .map_err(|_| ParseError::InvalidArgument(ip.to_string()))
+            })
+            .collect::<Result<Vec<usize>, ParseError>>()?;
+        Ok(Snapshot { state, trace })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SnapshotError> {
+        fs::write(path, self.to_text()).map_err(SnapshotError::Io)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SnapshotError> {
+        let text = fs::read_to_string(path).map_err(SnapshotError::Io)?;
+        Snapshot::parse(&text).map_err(SnapshotError::Malformed)
+    }
+}
+
+impl<'p> Machine<'p> {
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.cpu.state,
+            trace: self.trace.clone(),
+        }
+    }
+
+    fn restore(program: &'p Program, snapshot: &Snapshot) -> Self {
+        let mut machine = Machine::new(program);
+        machine.cpu.state = snapshot.state;
+        machine.trace = snapshot.trace.clone();
+        machine.visited = snapshot.trace.iter().map(|ip| *ip as isize).collect();
+        machine
+    }
+}
+
+struct Delta {
+    instruction_pointer_shift: isize,
+    accumulator_change: i64,
+    first_visit: bool,
+}
+
+pub struct TimeTravel<'p> {
+    machine: Machine<'p>,
+    deltas: Vec<Delta>,
+}
+
+impl<'p> TimeTravel<'p> {
+    pub fn new(program: &'p Program) -> Self {
+        TimeTravel {
+            machine: Machine::new(program),
+            deltas: Vec::new(),
+        }
+    }
+
+    pub fn restore(program: &'p Program, snapshot: &Snapshot) -> Self {
+        TimeTravel {
+            machine: Machine::restore(program, snapshot),
+            deltas: Vec::new(),
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.machine.snapshot()
+    }
+
+    pub fn state(&self) -> OptMachineState {
+        self.machine.cpu.state
+    }
+
+    pub fn steps(&self) -> usize {
+        self.deltas.len()
+    }
+
+    // Deltas cover only the instruction pointer and accumulator, so custom
+    // opcodes, which may write any register, are not stepped through.
+    pub fn step(&mut self) -> Option<OptMachineState> {
+        if let Some(Instruction::Custom(_)) = self.machine.current_instruction() {
+            return None;
+        }
+        let before = self.machine.cpu.state;
+        let first_visit = !self.machine.has_visited(before.instruction_pointer);
+        let after = self.machine.step()?;
+
+        self.deltas.push(Delta {
+            instruction_pointer_shift: after.instruction_pointer - before.instruction_pointer,
+            accumulator_change: after.accumulator - before.accumulator,
+            first_visit,
+        });
+        Some(after)
+    }
+
+    pub fn step_back(&mut self) -> Option<OptMachineState> {
+        let delta = self.deltas.pop()?;
+        let state = &mut self.machine.cpu.state;
+        state.instruction_pointer -= delta.instruction_pointer_shift;
+        state.accumulator -= delta.accumulator_change;
+
+        self.machine.trace.pop();
+        if delta.first_visit {
+            self.machine.visited.remove(&state.instruction_pointer);
+        }
+        Some(*state)
+    }
+
+    pub fn run(&mut self) {
+        while !self
+            .machine
+            .has_visited(self.machine.cpu.state.instruction_pointer)
+            && self.step().is_some()
+        {}
+    }
+
+    pub fn seek(&mut self, step: usize) -> OptMachineState {
+        while self.steps() > step && self.step_back().is_some() {}
+        while self.steps() < step && self.step().is_some() {}
+        self.machine.cpu.state
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    pub step: usize,
+    pub left: OptMachineState,
+    pub right: OptMachineState,
+}
+
+// Accumulators can differ and later agree again, so the runs are compared
+// step by step rather than bisected. A run that halts or loops keeps its last
+// accumulator while the other one carries on.
+pub fn first_divergence(left: &Program, right: &Program) -> Option<Divergence> {
+    let mut left = Machine::new(left);
+    let mut right = Machine::new(right);
+    let mut step = 0;
+
+    loop {
+        if left.cpu.state.accumulator != right.cpu.state.accumulator {
+            return Some(Divergence {
+                step,
+                left: left.cpu.state,
+                right: right.cpu.state,
+            });
+        }
+        let running = |machine: &Machine| {
+            machine.current_instruction().is_some()
+                && !machine.has_visited(machine.cpu.state.instruction_pointer)
+        };
+        let (left_running, right_running) = (running(&left), running(&right));
+        if !left_running && !right_running {
+            return None;
+        }
+        if left_running {
+            left.step();
+        }
+        if right_running {
+            right.step();
+        }
+        step += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Outcome, BOOT_CODE};
+    use super::*;
+
+    #[test]
+    fn snapshot_round_trips_through_text() {
+        let snapshot = Snapshot {
+            state: OptMachineState {
+                instruction_pointer: 7,
+                accumulator: -2,
+            },
+            trace: vec![0, 1, 2, 6],
+        };
+
+        assert_eq!(snapshot.to_text(), "ip 7\nacc -2\ntrace 0 1 2 6\n");
+        assert_eq!(Snapshot::parse(&snapshot.to_text()), Ok(snapshot));
+        assert_eq!(
+            Snapshot::parse("ip 7\ntrace 0"),
+            Err(ParseError::MissingArgument("acc".to_string()))
+        );
+    }
+
+    #[test]
+    fn save_snapshot_to_disk_and_resume_from_it() {
+        let program = Program::parse(BOOT_CODE).unwrap();
+        let mut machine = TimeTravel::new(&program);
+        machine.seek(4);
+
+        let path = std::env::temp_dir().join(format!("day_8_snapshot_{}", std::process::id()));
+        machine.snapshot().save(&path).unwrap();
+        let snapshot = Snapshot::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut resumed = Machine::restore(&program, &snapshot);
+        assert_eq!(resumed.run(), Machine::new(&program).run());
+    }
+
+    #[test]
+    fn loading_a_missing_snapshot_reports_io_error() {
+        let path = std::env::temp_dir().join("day_8_snapshot_that_does_not_exist");
+
+        assert!(matches!(Snapshot::load(&path), Err(SnapshotError::Io(_))));
+    }
+
+    #[test]
+    fn step_back_undoes_every_step() {
+        let program = Program::parse(BOOT_CODE).unwrap();
+        let mut machine = TimeTravel::new(&program);
+        machine.run();
+        let mut states = vec![machine.state()];
+        for _ in 0..machine.steps() {
+            machine.step_back();
+            states.push(machine.state());
+        }
+        let mut replayed = TimeTravel::new(&program);
+        let mut forward = vec![replayed.state()];
+        for _ in 0..7 {
+            forward.push(replayed.step().unwrap());
+        }
+
+        assert_eq!(states.len(), 8);
+        states.reverse();
+        assert_eq!(states, forward);
+        assert_eq!(machine.snapshot().trace, Vec::<usize>::new());
+        assert!(matches!(
+            Machine::restore(&program, &machine.snapshot()).run(),
+            Outcome::InfiniteLoop { acc: 5, .. }
+        ));
+    }
+
+    #[test]
+    fn bisect_step_where_accumulator_first_diverged() {
+        let left = Program::parse(BOOT_CODE).unwrap();
+        let right = Program::parse(&BOOT_CODE.replace("acc +3", "acc +4")).unwrap();
+
+        assert_eq!(
+            first_divergence(&left, &right),
+            Some(Divergence {
+                step: 6,
+                left: OptMachineState {
+                    instruction_pointer: 4,
+                    accumulator: 5
+                },
+                right: OptMachineState {
+                    instruction_pointer: 4,
+                    accumulator: 6
+                },
+            })
+        );
+        assert_eq!(first_divergence(&left, &left), None);
+    }
+
+    #[test]
+    fn find_divergence_even_when_accumulators_meet_again() {
+        let divergence_step = |left: &str, right: &str| {
+            let left = Program::parse(left).unwrap();
+            let right = Program::parse(right).unwrap();
+            first_divergence(&left, &right).map(|divergence| divergence.step)
+        };
+
+        assert_eq!(
+            divergence_step("acc +1\nacc +1\njmp +0", "acc +2\nacc +0\njmp +0"),
+            Some(1)
+        );
+        assert_eq!(
+            divergence_step("acc +1\nacc +0\nacc +5", "acc +2\nacc -1\nacc +6"),
+            Some(1)
+        );
+        assert_eq!(divergence_step("acc +1", "acc +1\nnop +0\nacc +5"), Some(3));
+        assert_eq!(divergence_step("acc +1\njmp +0", "acc +1\nacc +0"), None);
+    }
+}
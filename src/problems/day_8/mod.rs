@@ -0,0 +1,637 @@
+use opcodes::{Cpu, Effect, Operand, Operation};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+pub mod analysis;
+pub mod assembler;
+pub mod debugger;
+pub mod history;
+pub mod opcodes;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Acc(i64),
+    Jmp(i64),
+    Nop(i64),
+    Custom(Operation),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    UnknownOperation(String),
+    MissingArgument(String),
+    InvalidArgument(String),
+}
+
+impl Instruction {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut tokens = input.trim().split_ascii_whitespace();
+        let operation = tokens.next().unwrap_or_default();
+        let argument = tokens
+            .next()
+            .ok_or_else(|| ParseError::MissingArgument(input.trim().to_string()))?;
+        let argument = argument
+            .parse::<i64>()
+            .map_err(|_| ParseError::InvalidArgument(argument.to_string()))?;
+
+        match operation {
+            "acc" => Ok(Instruction::Acc(argument)),
+            "jmp" => Ok(Instruction::Jmp(argument)),
+            "nop" => Ok(Instruction::Nop(argument)),
+            _ => Err(ParseError::UnknownOperation(operation.to_string())),
+        }
+    }
+
+    fn flipped(&self) -> Option<Instruction> {
+        match *self {
+            Instruction::Acc(_) => None,
+            Instruction::Jmp(argument) => Some(Instruction::Nop(argument)),
+            Instruction::Nop(argument) => Some(Instruction::Jmp(argument)),
+            Instruction::Custom(_) => None,
+        }
+    }
+
+    // Custom opcodes that branch have no successor known before they run.
+    fn successor(&self, instruction_pointer: isize) -> Option<isize> {
+        match *self {
+            Instruction::Jmp(argument) => Some(instruction_pointer + argument as isize),
+            Instruction::Custom(operation) if operation.branches() => None,
+            _ => Some(instruction_pointer + 1),
+        }
+    }
+
+    fn operation(&self) -> Operation {
+        match *self {
+            Instruction::Acc(argument) => {
+                Operation::new(opcodes::ACC, &[Operand::Immediate(argument)])
+            }
+            Instruction::Jmp(argument) => {
+                Operation::new(opcodes::JMP, &[Operand::Immediate(argument)])
+            }
+            Instruction::Nop(argument) => {
+                Operation::new(opcodes::NOP, &[Operand::Immediate(argument)])
+            }
+            Instruction::Custom(operation) => operation,
+        }
+    }
+
+    fn execute(&self, cpu: &mut Cpu) -> Effect {
+        let effect = self.operation().execute(cpu);
+        let instruction_pointer = &mut cpu.state.instruction_pointer;
+        match effect {
+            Effect::Next => *instruction_pointer += 1,
+            Effect::Jump(offset) => {
+                *instruction_pointer = instruction_pointer.saturating_add(offset as isize)
+            }
+            Effect::AwaitInput | Effect::Overflow => {}
+        }
+        effect
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Acc(argument) => write!(f, "acc {:+}", argument),
+            Instruction::Jmp(argument) => write!(f, "jmp {:+}", argument),
+            Instruction::Nop(argument) => write!(f, "nop {:+}", argument),
+            Instruction::Custom(operation) => write!(f, "{}", operation),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+}
+
+impl Program {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let instructions = source
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Instruction::parse)
+            .collect::<Result<Vec<Instruction>, ParseError>>()?;
+        Ok(Program { instructions })
+    }
+
+    fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    fn instruction_at(&self, instruction_pointer: isize) -> Option<Instruction> {
+        usize::try_from(instruction_pointer)
+            .ok()
+            .and_then(|ip| self.instructions.get(ip))
+            .copied()
+    }
+
+    fn with_flipped(&self, index: usize) -> Option<Program> {
+        let flipped = self.instructions.get(index)?.flipped()?;
+        let mut instructions = self.instructions.clone();
+        instructions[index] = flipped;
+        Some(Program { instructions })
+    }
+
+    fn has_fixed_control_flow(&self) -> bool {
+        self.instructions
+            .iter()
+            .all(|instruction| match instruction {
+                Instruction::Custom(operation) => operation.has_fixed_control_flow(),
+                _ => true,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct OptMachineState {
+    pub instruction_pointer: isize,
+    pub accumulator: i64,
+}
+
+impl fmt::Display for OptMachineState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ip {}\nacc {}",
+            self.instruction_pointer, self.accumulator
+        )
+    }
+}
+
+impl FromStr for OptMachineState {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut instruction_pointer = None;
+        let mut accumulator = None;
+        for line in input.lines() {
+            let mut tokens = line.split_ascii_whitespace();
+            let (name, value) = match (tokens.next(), tokens.next()) {
+                (Some(name), Some(value)) => (name, value),
+                _ => continue,
+            };
+            let invalid = || ParseError::InvalidArgument(value.to_string());
+            match name {
+                "ip" => instruction_pointer = Some(value.parse().map_err(|_| invalid())?),
+                "acc" => accumulator = Some(value.parse().map_err(|_| invalid())?),
+                _ => {}
+            }
+        }
+
+        Ok(OptMachineState {
+            instruction_pointer: instruction_pointer
+                .ok_or_else(|| ParseError::MissingArgument("ip".to_string()))?,
+            accumulator: accumulator
+                .ok_or_else(|| ParseError::MissingArgument("acc".to_string()))?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Terminated {
+        acc: i64,
+    },
+    InfiniteLoop {
+        acc: i64,
+        ip: usize,
+        trace: Vec<usize>,
+    },
+    OutOfBounds {
+        ip: isize,
+    },
+    StepLimitExceeded,
+    AwaitingInput {
+        ip: usize,
+    },
+    Overflow {
+        ip: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Machine<'p> {
+    program: &'p Program,
+    cpu: Cpu,
+    visited: HashSet<isize>,
+    trace: Vec<usize>,
+    step_limit: Option<usize>,
+    stall: Option<Outcome>,
+}
+
+impl<'p> Machine<'p> {
+    fn new(program: &'p Program) -> Self {
+        Machine {
+            program,
+            cpu: Cpu::default(),
+            visited: HashSet::new(),
+            trace: Vec::new(),
+            step_limit: None,
+            stall: None,
+        }
+    }
+
+    fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = Some(step_limit);
+        self
+    }
+
+    fn with_input(mut self, input: &[i64]) -> Self {
+        self.cpu.input.extend(input);
+        self
+    }
+
+    fn current_instruction(&self) -> Option<Instruction> {
+        self.program
+            .instruction_at(self.cpu.state.instruction_pointer)
+    }
+
+    fn has_visited(&self, instruction_pointer: isize) -> bool {
+        self.visited.contains(&instruction_pointer)
+    }
+
+    // Instructions that cannot run leave the machine where it was and record
+    // the reason in `stall`.
+    fn step(&mut self) -> Option<OptMachineState> {
+        let instruction = self.current_instruction()?;
+        let instruction_pointer = self.cpu.state.instruction_pointer;
+        self.stall = match instruction.execute(&mut self.cpu) {
+            Effect::AwaitInput => Some(Outcome::AwaitingInput {
+                ip: instruction_pointer as usize,
+            }),
+            Effect::Overflow => Some(Outcome::Overflow {
+                ip: instruction_pointer as usize,
+            }),
+            Effect::Next | Effect::Jump(_) => None,
+        };
+        if self.stall.is_some() {
+            return None;
+        }
+        self.visited.insert(instruction_pointer);
+        self.trace.push(instruction_pointer as usize);
+        Some(self.cpu.state)
+    }
+
+    fn has_terminated(&self) -> bool {
+        self.cpu.state.instruction_pointer == self.program.len() as isize
+    }
+
+    // With branches on registers an instruction pointer can repeat outside a
+    // loop, so the whole machine state has to repeat instead.
+    fn run(&mut self) -> Outcome {
+        let fixed_control_flow = self.program.has_fixed_control_flow();
+        let mut seen = HashSet::new();
+        loop {
+            let ip = self.cpu.state.instruction_pointer;
+            if self.has_terminated() {
+                return Outcome::Terminated {
+                    acc: self.cpu.state.accumulator,
+                };
+            }
+            if self.current_instruction().is_none() {
+                return Outcome::OutOfBounds { ip };
+            }
+            let looped = if fixed_control_flow {
+                self.has_visited(ip)
+            } else {
+                let cpu = &self.cpu;
+                !seen.insert((cpu.state, cpu.registers, cpu.input.len()))
+            };
+            if looped {
+                return Outcome::InfiniteLoop {
+                    acc: self.cpu.state.accumulator,
+                    ip: ip as usize,
+                    trace: self.trace.clone(),
+                };
+            }
+            if self.step_limit.map(|limit| self.trace.len() >= limit) == Some(true) {
+                return Outcome::StepLimitExceeded;
+            }
+            if self.step().is_none() {
+                return self.stall.clone().unwrap_or(Outcome::OutOfBounds { ip });
+            }
+        }
+    }
+}
+
+fn accumulator_before_hang(instructions: &str) -> Result<Option<i64>, ParseError> {
+    let program = Program::parse(instructions)?;
+    match Machine::new(&program).run() {
+        Outcome::InfiniteLoop { acc, .. } => Ok(Some(acc)),
+        _ => Ok(None),
+    }
+}
+
+fn accumulator_after_machine_completes(instructions: &str) -> Result<Option<i64>, ParseError> {
+    let program = Program::parse(instructions)?;
+    match Machine::new(&program).run() {
+        Outcome::Terminated { acc } => Ok(Some(acc)),
+        _ => Ok(repair(&program, RepairStrategy::ReverseReachability).map(|r| r.accumulator)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RepairStrategy {
+    BruteForce,
+    ReverseReachability,
+}
+
+#[derive(Debug, PartialEq)]
+struct Repair {
+    flipped: usize,
+    accumulator: i64,
+}
+
+fn repair(program: &Program, strategy: RepairStrategy) -> Option<Repair> {
+    if terminates(program) {
+        return None;
+    }
+
+    let flipped = match strategy {
+        RepairStrategy::BruteForce => (0..program.len()).find(|index| {
+            program
+                .with_flipped(*index)
+                .map(|patched| terminates(&patched))
+                .unwrap_or(false)
+        }),
+        RepairStrategy::ReverseReachability => flip_reaching_termination(program),
+    }?;
+
+    match Machine::new(&program.with_flipped(flipped)?).run() {
+        Outcome::Terminated { acc } => Some(Repair {
+            flipped,
+            accumulator: acc,
+        }),
+        _ => None,
+    }
+}
+
+fn terminates(program: &Program) -> bool {
+    matches!(Machine::new(program).run(), Outcome::Terminated { .. })
+}
+
+// Instructions that reach the end form a tree rooted at the end once the
+// edges are reversed; entry and exit times of a walk over that tree tell
+// whether the path from one instruction passes through another. Instructions
+// that never reach the end are not entered.
+fn termination_tree(instructions: &[Instruction]) -> (Vec<Option<usize>>, Vec<usize>) {
+    let end = instructions.len();
+    let mut children = vec![Vec::new(); end + 1];
+    instructions
+        .iter()
+        .enumerate()
+        .for_each(|(index, instruction)| {
+            if let Some(successor) = instruction
+                .successor(index as isize)
+                .filter(|successor| (0..=end as isize).contains(successor))
+            {
+                children[successor as usize].push(index);
+            }
+        });
+
+    let mut entered = vec![None; end + 1];
+    let mut exited = vec![0; end + 1];
+    let mut clock = 0;
+    let mut stack = vec![(end, false)];
+    while let Some((index, is_exit)) = stack.pop() {
+        clock += 1;
+        if is_exit {
+            exited[index] = clock;
+            continue;
+        }
+        entered[index] = Some(clock);
+        stack.push((index, true));
+        children[index]
+            .iter()
+            .for_each(|child| stack.push((*child, false)));
+    }
+    (entered, exited)
+}
+
+fn terminating_instructions(program: &Program) -> Vec<bool> {
+    let (entered, _) = termination_tree(&program.instructions);
+    entered.iter().map(Option::is_some).collect()
+}
+
+fn flip_reaching_termination(program: &Program) -> Option<usize> {
+    let terminating = terminating_instructions(program);
+    let mut machine = Machine::new(program);
+
+    loop {
+        let instruction_pointer = machine.cpu.state.instruction_pointer;
+        if machine.has_visited(instruction_pointer) {
+            return None;
+        }
+        let instruction = machine.current_instruction()?;
+        let reaches_termination = instruction
+            .flipped()
+            .and_then(|flipped| flipped.successor(instruction_pointer))
+            .and_then(|successor| usize::try_from(successor).ok())
+            .map(|successor| terminating.get(successor) == Some(&true))
+            .unwrap_or(false);
+        if reaches_termination {
+            return Some(instruction_pointer as usize);
+        }
+        machine.step();
+    }
+}
+
+#[cfg(test)]
+const BOOT_CODE: &str = "nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_a_given_instruction() {
+        assert_eq!(Instruction::parse("acc +1"), Ok(Instruction::Acc(1)));
+        assert_eq!(Instruction::parse("jmp +4"), Ok(Instruction::Jmp(4)));
+        assert_eq!(Instruction::parse("acc -99"), Ok(Instruction::Acc(-99)));
+    }
+
+    #[test]
+    fn parse_a_program_once_and_run_it_many_times() {
+        let program = Program::parse(BOOT_CODE).unwrap();
+        assert_eq!(program.len(), 9);
+
+        let first_run = Machine::new(&program).run();
+        let second_run = Machine::new(&program).run();
+        assert_eq!(
+            first_run,
+            Outcome::InfiniteLoop {
+                acc: 5,
+                ip: 1,
+                trace: vec![0, 1, 2, 6, 7, 3, 4]
+            }
+        );
+        assert_eq!(first_run, second_run);
+
+        let parallel_runs: Vec<Outcome> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| Machine::new(&program).run()))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        assert!(parallel_runs.iter().all(|outcome| *outcome == first_run));
+    }
+
+    #[test]
+    fn machine_steps_through_program() {
+        let program = Program::parse("acc +2\njmp -1").unwrap();
+        let mut machine = Machine::new(&program);
+
+        assert_eq!(machine.current_instruction(), Some(Instruction::Acc(2)));
+        machine.step();
+        assert_eq!(machine.current_instruction(), Some(Instruction::Jmp(-1)));
+        machine.step();
+        assert!(machine.has_visited(machine.cpu.state.instruction_pointer));
+        assert_eq!(machine.cpu.state.accumulator, 2);
+    }
+
+    #[test]
+    fn give_error_for_malformed_instructions() {
+        assert_eq!(
+            Instruction::parse("mul +2"),
+            Err(ParseError::UnknownOperation("mul".to_string()))
+        );
+        assert_eq!(
+            Instruction::parse("acc"),
+            Err(ParseError::MissingArgument("acc".to_string()))
+        );
+        assert_eq!(
+            Instruction::parse("jmp four"),
+            Err(ParseError::InvalidArgument("four".to_string()))
+        );
+        assert_eq!(
+            accumulator_before_hang("nop +0\nhlt +0"),
+            Err(ParseError::UnknownOperation("hlt".to_string()))
+        );
+    }
+
+    #[test]
+    fn get_accumulator_accout_before_machine_hangs() {
+        assert_eq!(accumulator_before_hang(BOOT_CODE), Ok(Some(5)));
+        assert_eq!(accumulator_before_hang("acc +1\nacc +2"), Ok(None));
+    }
+
+    #[test]
+    fn get_accumulator_count_after_machine_completes_by_skiping_hung() {
+        let input = BOOT_CODE.replace("jmp -4", "nop -4");
+        assert_eq!(accumulator_after_machine_completes(&input), Ok(Some(8)));
+    }
+
+    #[test]
+    fn repair_program_by_flipping_a_single_instruction() {
+        let program = Program::parse(BOOT_CODE).unwrap();
+        let expected = Some(Repair {
+            flipped: 7,
+            accumulator: 8,
+        });
+
+        assert_eq!(repair(&program, RepairStrategy::BruteForce), expected);
+        assert_eq!(
+            repair(&program, RepairStrategy::ReverseReachability),
+            expected
+        );
+    }
+
+    #[test]
+    fn repair_flips_the_instruction_instead_of_skipping_executed_ones() {
+        let input = "acc +3
+        jmp +3
+        acc +1
+        acc +2
+        jmp -2
+        acc -2";
+        let program = Program::parse(input).unwrap();
+        let expected = Some(Repair {
+            flipped: 4,
+            accumulator: 1,
+        });
+
+        assert_eq!(repair(&program, RepairStrategy::BruteForce), expected);
+        assert_eq!(
+            repair(&program, RepairStrategy::ReverseReachability),
+            expected
+        );
+        assert_eq!(accumulator_after_machine_completes(input), Ok(Some(1)));
+    }
+
+    #[test]
+    fn give_none_when_no_single_flip_terminates_the_program() {
+        let program = Program::parse("jmp +0\njmp -1\njmp -1").unwrap();
+
+        assert_eq!(repair(&program, RepairStrategy::BruteForce), None);
+        assert_eq!(repair(&program, RepairStrategy::ReverseReachability), None);
+    }
+
+    #[test]
+    fn give_none_when_program_already_terminates() {
+        let program = Program::parse("nop +0\nacc +1").unwrap();
+
+        assert_eq!(repair(&program, RepairStrategy::BruteForce), None);
+        assert_eq!(repair(&program, RepairStrategy::ReverseReachability), None);
+    }
+
+    #[test]
+    fn run_reports_termination() {
+        let program = Program::parse("acc +3\nnop +0\nacc -1").unwrap();
+
+        assert_eq!(Machine::new(&program).run(), Outcome::Terminated { acc: 2 });
+    }
+
+    #[test]
+    fn run_reports_instruction_pointer_leaving_the_program() {
+        let beyond_end = Program::parse("acc +1\njmp +5").unwrap();
+        let before_start = Program::parse("acc +1\njmp -2").unwrap();
+
+        assert_eq!(
+            Machine::new(&beyond_end).run(),
+            Outcome::OutOfBounds { ip: 6 }
+        );
+        assert_eq!(
+            Machine::new(&before_start).run(),
+            Outcome::OutOfBounds { ip: -1 }
+        );
+        assert_eq!(accumulator_before_hang("acc +1\njmp -2"), Ok(None));
+    }
+
+    #[test]
+    fn run_reports_accumulator_overflow() {
+        let program = Program::parse("acc +9223372036854775807\nacc +1").unwrap();
+        let mut machine = Machine::new(&program);
+
+        assert_eq!(machine.run(), Outcome::Overflow { ip: 1 });
+        assert_eq!(machine.cpu.state.accumulator, i64::MAX);
+        assert_eq!(machine.trace, vec![0]);
+    }
+
+    #[test]
+    fn run_stops_when_step_limit_is_exceeded() {
+        let program = Program::parse("nop +0\nnop +0\nnop +0\nnop +0").unwrap();
+
+        assert_eq!(
+            Machine::new(&program).with_step_limit(2).run(),
+            Outcome::StepLimitExceeded
+        );
+        assert_eq!(
+            Machine::new(&program).with_step_limit(4).run(),
+            Outcome::Terminated { acc: 0 }
+        );
+    }
+}
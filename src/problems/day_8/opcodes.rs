@@ -0,0 +1,588 @@
+use super::{Instruction, OptMachineState, ParseError, Program};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+pub const REGISTERS: usize = 4;
+const ACCUMULATOR: usize = 0;
+const MAX_OPERANDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    Register(usize),
+    Immediate(i64),
+}
+
+impl Operand {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let input = input.trim_end_matches(',');
+        match input.as_bytes() {
+            [register @ b'a'..=b'd'] => Ok(Operand::Register((register - b'a') as usize)),
+            _ => input
+                .parse::<i64>()
+                .map(Operand::Immediate)
+                .map_err(|_| ParseError::InvalidArgument(input.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Register(register) => write!(f, "{}", (b'a' + *register as u8) as char),
+            Operand::Immediate(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+// Register `a` is the accumulator of the machine state; `registers` holds
+// `b` to `d`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cpu {
+    pub state: OptMachineState,
+    pub registers: [i64; REGISTERS - 1],
+    pub input: VecDeque<i64>,
+    pub output: Vec<i64>,
+}
+
+impl Cpu {
+    pub fn value_of(&self, operand: Operand) -> i64 {
+        match operand {
+            Operand::Register(ACCUMULATOR) => self.state.accumulator,
+            Operand::Register(register) => self.registers[register - 1],
+            Operand::Immediate(value) => value,
+        }
+    }
+
+    pub fn register_mut(&mut self, operand: Operand) -> Option<&mut i64> {
+        match operand {
+            Operand::Register(ACCUMULATOR) => Some(&mut self.state.accumulator),
+            Operand::Register(register) => self.registers.get_mut(register - 1),
+            Operand::Immediate(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    Next,
+    Jump(i64),
+    AwaitInput,
+    Overflow,
+}
+
+#[derive(Clone, Copy)]
+pub struct OpcodeSpec {
+    pub mnemonic: &'static str,
+    pub arity: usize,
+    pub write_targets: &'static [usize],
+    pub branches: bool,
+    pub execute: fn(&[Operand], &mut Cpu) -> Effect,
+}
+
+pub const ACC: OpcodeSpec = OpcodeSpec {
+    mnemonic: "acc",
+    arity: 1,
+    write_targets: &[],
+    branches: false,
+    execute: |operands, cpu| match cpu.state.accumulator.checked_add(cpu.value_of(operands[0])) {
+        Some(accumulator) => {
+            cpu.state.accumulator = accumulator;
+            Effect::Next
+        }
+        None => Effect::Overflow,
+    },
+};
+
+pub const JMP: OpcodeSpec = OpcodeSpec {
+    mnemonic: "jmp",
+    arity: 1,
+    write_targets: &[],
+    branches: true,
+    execute: |operands, cpu| Effect::Jump(cpu.value_of(operands[0])),
+};
+
+pub const NOP: OpcodeSpec = OpcodeSpec {
+    mnemonic: "nop",
+    arity: 1,
+    write_targets: &[],
+    branches: false,
+    execute: |_, _| Effect::Next,
+};
+
+#[derive(Clone, Copy)]
+pub struct Operation {
+    spec: OpcodeSpec,
+    operands: [Operand; MAX_OPERANDS],
+}
+
+impl Operation {
+    pub(super) fn new(spec: OpcodeSpec, operands: &[Operand]) -> Self {
+        let mut padded = [Operand::Immediate(0); MAX_OPERANDS];
+        padded[..operands.len()].copy_from_slice(operands);
+        Operation {
+            spec,
+            operands: padded,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        self.spec.mnemonic
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.spec.arity]
+    }
+
+    pub fn branches(&self) -> bool {
+        self.spec.branches
+    }
+
+    // Branches on immediates take the same path every time, so only branches
+    // on registers make the path depend on the machine state.
+    pub fn has_fixed_control_flow(&self) -> bool {
+        !self.branches()
+            || self
+                .operands()
+                .iter()
+                .all(|operand| matches!(operand, Operand::Immediate(_)))
+    }
+
+    pub(super) fn execute(&self, cpu: &mut Cpu) -> Effect {
+        (self.spec.execute)(self.operands(), cpu)
+    }
+}
+
+impl fmt::Debug for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Operation")
+            .field("mnemonic", &self.mnemonic())
+            .field("operands", &self.operands())
+            .finish()
+    }
+}
+
+impl PartialEq for Operation {
+    fn eq(&self, other: &Self) -> bool {
+        self.mnemonic() == other.mnemonic() && self.operands() == other.operands()
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        self.operands()
+            .iter()
+            .try_for_each(|operand| write!(f, " {}", operand))
+    }
+}
+
+pub struct OpcodeRegistry {
+    opcodes: HashMap<&'static str, OpcodeSpec>,
+    handheld: HashSet<&'static str>,
+}
+
+impl OpcodeRegistry {
+    pub fn empty() -> Self {
+        OpcodeRegistry {
+            opcodes: HashMap::new(),
+            handheld: HashSet::new(),
+        }
+    }
+
+    pub fn handheld() -> Self {
+        let mut registry = OpcodeRegistry::empty();
+        for spec in [ACC, JMP, NOP] {
+            registry.opcodes.insert(spec.mnemonic, spec);
+            registry.handheld.insert(spec.mnemonic);
+        }
+        registry
+    }
+
+    #[cfg(feature = "extended-opcodes")]
+    pub fn extended() -> Self {
+        let mut registry = OpcodeRegistry::handheld();
+        let arithmetic = |mnemonic, execute| OpcodeSpec {
+            mnemonic,
+            arity: 2,
+            write_targets: &[0],
+            branches: false,
+            execute,
+        };
+        registry.register(arithmetic("set", |operands, cpu| {
+            let value = cpu.value_of(operands[1]);
+            if let Some(target) = cpu.register_mut(operands[0]) {
+                *target = value;
+            }
+            Effect::Next
+        }));
+        registry.register(arithmetic("add", |operands, cpu| {
+            let value = cpu.value_of(operands[1]);
+            if let Some(target) = cpu.register_mut(operands[0]) {
+                match target.checked_add(value) {
+                    Some(sum) => *target = sum,
+                    None => return Effect::Overflow,
+                }
+            }
+            Effect::Next
+        }));
+        registry.register(arithmetic("mul", |operands, cpu| {
+            let value = cpu.value_of(operands[1]);
+            if let Some(target) = cpu.register_mut(operands[0]) {
+                match target.checked_mul(value) {
+                    Some(product) => *target = product,
+                    None => return Effect::Overflow,
+                }
+            }
+            Effect::Next
+        }));
+        registry.register(OpcodeSpec {
+            mnemonic: "jz",
+            arity: 2,
+            write_targets: &[],
+            branches: true,
+            execute: |operands, cpu| match cpu.value_of(operands[0]) {
+                0 => Effect::Jump(cpu.value_of(operands[1])),
+                _ => Effect::Next,
+            },
+        });
+        registry.register(OpcodeSpec {
+            mnemonic: "jnz",
+            arity: 2,
+            write_targets: &[],
+            branches: true,
+            execute: |operands, cpu| match cpu.value_of(operands[0]) {
+                0 => Effect::Next,
+                _ => Effect::Jump(cpu.value_of(operands[1])),
+            },
+        });
+        registry.register(OpcodeSpec {
+            mnemonic: "out",
+            arity: 1,
+            write_targets: &[],
+            branches: false,
+            execute: |operands, cpu| {
+                let value = cpu.value_of(operands[0]);
+                cpu.output.push(value);
+                Effect::Next
+            },
+        });
+        registry.register(OpcodeSpec {
+            mnemonic: "in",
+            arity: 1,
+            write_targets: &[0],
+            branches: false,
+            execute: |operands, cpu| match cpu.input.pop_front() {
+                Some(value) => {
+                    if let Some(target) = cpu.register_mut(operands[0]) {
+                        *target = value;
+                    }
+                    Effect::Next
+                }
+                None => Effect::AwaitInput,
+            },
+        });
+        registry
+    }
+
+    pub fn register(&mut self, spec: OpcodeSpec) -> Option<OpcodeSpec> {
+        self.handheld.remove(spec.mnemonic);
+        self.opcodes.insert(spec.mnemonic, spec)
+    }
+
+    fn parse_instruction(&self, input: &str) -> Result<Instruction, ParseError> {
+        let mut tokens = input.split_ascii_whitespace();
+        let mnemonic = tokens.next().unwrap_or_default();
+        let spec = *self
+            .opcodes
+            .get(mnemonic)
+            .ok_or_else(|| ParseError::UnknownOperation(mnemonic.to_string()))?;
+        let operands = tokens
+            .map(Operand::parse)
+            .collect::<Result<Vec<Operand>, ParseError>>()?;
+
+        if operands.len() < spec.arity {
+            return Err(ParseError::MissingArgument(input.to_string()));
+        }
+        if operands.len() > spec.arity || operands.len() > MAX_OPERANDS {
+            return Err(ParseError::InvalidArgument(input.to_string()));
+        }
+        let writes_registers = spec
+            .write_targets
+            .iter()
+            .all(|target| matches!(operands.get(*target), Some(Operand::Register(_))));
+        if !writes_registers {
+            return Err(ParseError::InvalidArgument(input.to_string()));
+        }
+
+        let handheld = self.handheld.contains(mnemonic);
+        Ok(match (mnemonic, operands.as_slice()) {
+            ("acc", [Operand::Immediate(argument)]) if handheld => Instruction::Acc(*argument),
+            ("jmp", [Operand::Immediate(argument)]) if handheld => Instruction::Jmp(*argument),
+            ("nop", [Operand::Immediate(argument)]) if handheld => Instruction::Nop(*argument),
+            _ => Instruction::Custom(Operation::new(spec, &operands)),
+        })
+    }
+
+    pub fn parse(&self, source: &str) -> Result<Program, ParseError> {
+        let instructions = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| self.parse_instruction(line))
+            .collect::<Result<Vec<Instruction>, ParseError>>()?;
+        Ok(Program { instructions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::analysis::ControlFlowGraph;
+    use super::super::history::TimeTravel;
+    use super::super::{Machine, Outcome, BOOT_CODE};
+    use super::*;
+
+    #[test]
+    fn handheld_registry_parses_the_handheld_instructions() {
+        let registry = OpcodeRegistry::handheld();
+
+        assert_eq!(registry.parse(BOOT_CODE), Program::parse(BOOT_CODE));
+        assert_eq!(
+            Machine::new(&registry.parse("acc +2\nacc b\nnop +0").unwrap()).run(),
+            Outcome::Terminated { acc: 2 }
+        );
+    }
+
+    #[test]
+    fn handheld_instructions_execute_through_their_opcodes() {
+        let mut cpu = Cpu::default();
+
+        assert_eq!(Instruction::Acc(3).execute(&mut cpu), Effect::Next);
+        assert_eq!(Instruction::Jmp(-2).execute(&mut cpu), Effect::Jump(-2));
+        assert_eq!(
+            cpu.state,
+            OptMachineState {
+                instruction_pointer: -1,
+                accumulator: 3
+            }
+        );
+    }
+
+    #[test]
+    fn register_custom_opcode() {
+        let mut registry = OpcodeRegistry::handheld();
+        registry.register(OpcodeSpec {
+            mnemonic: "dbl",
+            arity: 0,
+            write_targets: &[],
+            branches: false,
+            execute: |_, cpu| {
+                cpu.state.accumulator *= 2;
+                Effect::Next
+            },
+        });
+        let program = registry.parse("acc +3\ndbl\ndbl\nacc -1").unwrap();
+
+        assert_eq!(
+            Machine::new(&program).run(),
+            Outcome::Terminated { acc: 11 }
+        );
+        assert_eq!(program.instructions[1].to_string(), "dbl");
+        assert!(ControlFlowGraph::build(&program).is_some());
+    }
+
+    #[test]
+    fn graph_and_history_stop_at_custom_opcodes() {
+        let mut registry = OpcodeRegistry::handheld();
+        registry.register(OpcodeSpec {
+            mnemonic: "jz",
+            arity: 2,
+            write_targets: &[],
+            branches: true,
+            execute: |operands, cpu| match cpu.value_of(operands[0]) {
+                0 => Effect::Jump(cpu.value_of(operands[1])),
+                _ => Effect::Next,
+            },
+        });
+        let program = registry.parse("acc +1\njz a +2\nacc +1").unwrap();
+
+        assert!(ControlFlowGraph::build(&program).is_none());
+        let mut history = TimeTravel::new(&program);
+        assert!(history.step().is_some());
+        assert_eq!(history.step(), None);
+        assert_eq!(Machine::new(&program).run(), Outcome::Terminated { acc: 2 });
+    }
+
+    #[test]
+    fn overriding_a_handheld_opcode_runs_the_new_one() {
+        let mut registry = OpcodeRegistry::handheld();
+        registry.register(OpcodeSpec {
+            execute: |operands, cpu| {
+                cpu.state.accumulator -= cpu.value_of(operands[0]);
+                Effect::Next
+            },
+            ..ACC
+        });
+
+        assert_eq!(
+            Machine::new(&registry.parse("acc +3\nacc +1").unwrap()).run(),
+            Outcome::Terminated { acc: -4 }
+        );
+    }
+
+    #[test]
+    fn reject_write_targets_that_are_not_register_operands() {
+        let mut registry = OpcodeRegistry::handheld();
+        registry.register(OpcodeSpec {
+            mnemonic: "clr",
+            arity: 0,
+            write_targets: &[0],
+            branches: false,
+            execute: |_, _| Effect::Next,
+        });
+        registry.register(OpcodeSpec {
+            mnemonic: "swp",
+            arity: 2,
+            write_targets: &[0, 1],
+            branches: false,
+            execute: |operands, cpu| {
+                let (first, second) = (cpu.value_of(operands[0]), cpu.value_of(operands[1]));
+                if let Some(target) = cpu.register_mut(operands[0]) {
+                    *target = second;
+                }
+                if let Some(target) = cpu.register_mut(operands[1]) {
+                    *target = first;
+                }
+                Effect::Next
+            },
+        });
+
+        assert_eq!(
+            registry.parse("clr").err(),
+            Some(ParseError::InvalidArgument("clr".to_string()))
+        );
+        assert_eq!(
+            registry.parse("swp a 3").err(),
+            Some(ParseError::InvalidArgument("swp a 3".to_string()))
+        );
+        let program = registry.parse("acc +2\nswp a b\nacc +1").unwrap();
+        assert_eq!(Machine::new(&program).run(), Outcome::Terminated { acc: 1 });
+        assert_eq!(Cpu::default().register_mut(Operand::Immediate(3)), None);
+    }
+
+    #[test]
+    fn report_unknown_opcodes_and_bad_operands() {
+        let registry = OpcodeRegistry::handheld();
+
+        assert_eq!(
+            registry.parse("mul a 2").err(),
+            Some(ParseError::UnknownOperation("mul".to_string()))
+        );
+        assert_eq!(
+            registry.parse("acc").err(),
+            Some(ParseError::MissingArgument("acc".to_string()))
+        );
+        assert_eq!(
+            registry.parse("jmp +1 +2").err(),
+            Some(ParseError::InvalidArgument("jmp +1 +2".to_string()))
+        );
+        assert_eq!(
+            registry.parse("acc x").err(),
+            Some(ParseError::InvalidArgument("x".to_string()))
+        );
+    }
+
+    #[cfg(feature = "extended-opcodes")]
+    #[test]
+    fn extended_opcodes_use_registers_and_conditional_jumps() {
+        let program = OpcodeRegistry::extended()
+            .parse(
+                "set b 5
+                set a 1
+                mul a b
+                add b -1
+                jnz b -2
+                out a",
+            )
+            .unwrap();
+        let mut machine = Machine::new(&program);
+
+        assert_eq!(machine.run(), Outcome::Terminated { acc: 120 });
+        assert_eq!(machine.cpu.output, vec![120]);
+        assert!(ControlFlowGraph::build(&program).is_none());
+    }
+
+    #[cfg(feature = "extended-opcodes")]
+    #[test]
+    fn extended_machine_reads_input_and_waits_when_it_runs_out() {
+        let program = OpcodeRegistry::extended()
+            .parse(
+                "in b
+                jz b +3
+                acc b
+                jmp -3
+                out a",
+            )
+            .unwrap();
+
+        let mut machine = Machine::new(&program).with_input(&[4, 7, 0]);
+        assert_eq!(machine.run(), Outcome::Terminated { acc: 11 });
+        assert_eq!(machine.cpu.output, vec![11]);
+
+        let mut machine = Machine::new(&program).with_input(&[4]);
+        assert_eq!(machine.run(), Outcome::AwaitingInput { ip: 0 });
+        assert_eq!(machine.cpu.state.accumulator, 4);
+        machine.cpu.input.push_back(0);
+        assert_eq!(machine.run(), Outcome::Terminated { acc: 4 });
+    }
+
+    #[cfg(feature = "extended-opcodes")]
+    #[test]
+    fn extended_machine_detects_repeated_state_and_step_limit() {
+        let registry = OpcodeRegistry::extended();
+        let looping = registry.parse("set b 1\njnz b +0").unwrap();
+        let counting = registry.parse("add a 1\njnz a -1").unwrap();
+
+        assert_eq!(
+            Machine::new(&looping).run(),
+            Outcome::InfiniteLoop {
+                acc: 0,
+                ip: 1,
+                trace: vec![0, 1]
+            }
+        );
+        assert_eq!(
+            Machine::new(&counting).with_step_limit(100).run(),
+            Outcome::StepLimitExceeded
+        );
+    }
+
+    #[cfg(feature = "extended-opcodes")]
+    #[test]
+    fn extended_arithmetic_reports_overflow() {
+        let program = OpcodeRegistry::extended()
+            .parse("set a 2\nset b 1\nmul a a\njnz b -1")
+            .unwrap();
+        let mut machine = Machine::new(&program).with_step_limit(1000);
+
+        assert_eq!(machine.run(), Outcome::Overflow { ip: 2 });
+        assert_eq!(machine.cpu.state.accumulator, 1 << 32);
+        assert_eq!(
+            Machine::new(
+                &OpcodeRegistry::extended()
+                    .parse("set a 9223372036854775807\nadd a 1")
+                    .unwrap()
+            )
+            .run(),
+            Outcome::Overflow { ip: 1 }
+        );
+    }
+
+    #[cfg(feature = "extended-opcodes")]
+    #[test]
+    fn extended_opcodes_require_a_register_target() {
+        assert_eq!(
+            OpcodeRegistry::extended().parse("add 3 4").err(),
+            Some(ParseError::InvalidArgument("add 3 4".to_string()))
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use super::day_1::pair_in_window_having_sum_n;
+
+fn parse_numbers(input: &str) -> Option<Vec<i64>> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse::<i64>().ok())
+        .collect()
+}
+
+fn first_invalid_number(numbers: &[i64], preamble: usize) -> Option<i64> {
+    numbers
+        .windows(preamble + 1)
+        .find(|window| pair_in_window_having_sum_n(window[preamble], &window[..preamble]).is_none())
+        .map(|window| window[preamble])
+}
+
+// Growing the run on the right and shrinking it on the left only ever moves
+// towards the target because the numbers are non-negative.
+fn contiguous_run_having_sum(sum: i64, numbers: &[i64]) -> Option<&[i64]> {
+    let mut start = 0;
+    let mut run_sum = 0;
+
+    for end in 0..numbers.len() {
+        run_sum += numbers[end];
+        while run_sum > sum && start < end {
+            run_sum -= numbers[start];
+            start += 1;
+        }
+        if run_sum == sum && end > start {
+            return Some(&numbers[start..=end]);
+        }
+    }
+    None
+}
+
+fn encryption_weakness(numbers: &[i64], preamble: usize) -> Option<i64> {
+    let invalid = first_invalid_number(numbers, preamble)?;
+    let run = contiguous_run_having_sum(invalid, numbers)?;
+    Some(run.iter().min()? + run.iter().max()?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NUMBERS: &str = "35
+20
+15
+25
+47
+40
+62
+55
+65
+95
+102
+117
+150
+182
+127
+219
+299
+277
+309
+576";
+
+    #[test]
+    fn find_first_number_not_sum_of_two_previous() {
+        let numbers = parse_numbers(NUMBERS).unwrap();
+
+        assert_eq!(first_invalid_number(&numbers, 5), Some(127));
+        assert_eq!(first_invalid_number(&numbers[..14], 5), None);
+    }
+
+    #[test]
+    fn preamble_of_25_accepts_any_pair_of_previous_numbers() {
+        let mut numbers: Vec<i64> = (1..=25).collect();
+        numbers.extend(vec![26, 49, 100, 50]);
+
+        assert_eq!(first_invalid_number(&numbers, 25), Some(100));
+    }
+
+    #[test]
+    fn find_contiguous_run_summing_to_invalid_number() {
+        let numbers = parse_numbers(NUMBERS).unwrap();
+
+        assert_eq!(
+            contiguous_run_having_sum(127, &numbers),
+            Some(&[15, 25, 47, 40][..])
+        );
+        assert_eq!(contiguous_run_having_sum(36, &numbers), None);
+        assert_eq!(encryption_weakness(&numbers, 5), Some(62));
+    }
+}
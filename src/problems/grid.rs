@@ -0,0 +1,149 @@
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+impl<T> Grid<T> {
+    pub fn parse<F>(input: &str, cell: F) -> Option<Self>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut cells = Vec::new();
+        for line in input.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let before = cells.len();
+            for c in line.chars() {
+                cells.push(cell(c)?);
+            }
+            if *width.get_or_insert(cells.len() - before) != cells.len() - before {
+                return None;
+            }
+        }
+        let width = width?;
+        Some(Grid {
+            width,
+            height: cells.len() / width,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<&T> {
+        if row < self.height && column < self.width {
+            Some(&self.cells[row * self.width + column])
+        } else {
+            None
+        }
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn index_of(&self, row: usize, column: usize) -> usize {
+        row * self.width + column
+    }
+
+    pub fn offset(
+        &self,
+        (row, column): (usize, usize),
+        (row_step, column_step): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let row = (row as isize).checked_add(row_step)?;
+        let column = (column as isize).checked_add(column_step)?;
+        if row < 0 || column < 0 || row as usize >= self.height || column as usize >= self.width {
+            return None;
+        }
+        Some((row as usize, column as usize))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    pub fn with_cells(&self, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), self.cells.len(), "grid size must not change");
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells,
+        }
+    }
+
+    pub fn render<F>(&self, cell: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut rendered = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width) {
+            rendered.extend(row.iter().map(&cell));
+            rendered.push('\n');
+        }
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_and_render_round_trip() {
+        let grid = Grid::parse("..#\n#..", |c| Some(c == '#')).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(1, 0), Some(&true));
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(
+            grid.render(|tree| if *tree { '#' } else { '.' }),
+            "..#\n#..\n"
+        );
+    }
+
+    #[test]
+    fn reject_ragged_rows_and_unknown_cells() {
+        assert_eq!(Grid::parse("..#\n#.", |c| Some(c == '#')), None);
+        assert_eq!(Grid::parse("..x", |c| Some(c).filter(|c| *c != 'x')), None);
+    }
+
+    #[test]
+    fn offsets_stay_inside_the_grid() {
+        let grid = Grid::parse("...\n...", Some).unwrap();
+
+        assert_eq!(grid.offset((0, 0), (1, 1)), Some((1, 1)));
+        assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.offset((1, 2), (0, 1)), None);
+    }
+}
@@ -3,5 +3,18 @@ mod day_2;
 mod day_3;
 mod day_4;
 mod day_5;
-mod day_6;
-mod day_8;
+pub mod day_6;
+mod day_7;
+pub mod day_8;
+mod day_9;
+mod day_10;
+mod day_11;
+mod day_12;
+mod day_13;
+mod day_14;
+pub mod day_15;
+mod day_16;
+mod day_17;
+mod grid;
+mod number_theory;
+mod validation;
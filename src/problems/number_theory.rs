@@ -0,0 +1,99 @@
+pub fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn lcm(a: i128, b: i128) -> i128 {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        (a / gcd(a, b) * b).abs()
+    }
+}
+
+// Returns (g, x, y) such that a * x + b * y = g = gcd(a, b).
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+pub fn mod_inverse(a: i128, modulus: i128) -> Option<i128> {
+    if modulus <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    if g == 1 {
+        Some(x.rem_euclid(modulus))
+    } else {
+        None
+    }
+}
+
+// Merges x = a (mod m) and x = b (mod n) into a single congruence modulo
+// lcm(m, n). The moduli need not be coprime; congruences that disagree on
+// their common factor have no solution, and so do non-positive moduli.
+pub fn combine_congruences((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    if m <= 0 || n <= 0 {
+        return None;
+    }
+    let (g, p, _) = extended_gcd(m, n);
+    let difference = b - a;
+    if difference % g != 0 {
+        return None;
+    }
+    let step = n / g;
+    let k = (difference / g).rem_euclid(step) * p.rem_euclid(step) % step;
+    let modulus = m / g * n;
+    Some(((a + m * k).rem_euclid(modulus), modulus))
+}
+
+pub fn chinese_remainder(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    congruences
+        .iter()
+        .try_fold((0, 1), |solution, (residue, modulus)| {
+            if *modulus <= 0 {
+                return None;
+            }
+            combine_congruences(solution, (residue.rem_euclid(*modulus), *modulus))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gcd_lcm_and_inverse() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+        assert_eq!(mod_inverse(3, 7), Some(5));
+        assert_eq!(mod_inverse(4, 8), None);
+    }
+
+    #[test]
+    fn solve_coprime_and_non_coprime_systems() {
+        assert_eq!(
+            chinese_remainder(&[(2, 3), (3, 5), (2, 7)]),
+            Some((23, 105))
+        );
+        assert_eq!(chinese_remainder(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(chinese_remainder(&[(1, 4), (2, 6)]), None);
+        assert_eq!(chinese_remainder(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn reject_non_positive_moduli() {
+        assert_eq!(chinese_remainder(&[(1, 3), (0, 0)]), None);
+        assert_eq!(chinese_remainder(&[(1, -5)]), None);
+        assert_eq!(combine_congruences((0, 0), (0, 0)), None);
+        assert_eq!(mod_inverse(3, 0), None);
+    }
+}
@@ -0,0 +1,69 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl ValueRange {
+    pub const fn new(min: usize, max: usize) -> Self {
+        ValueRange { min, max }
+    }
+
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut bounds = input.trim().split('-');
+        let min = bounds.next()?.parse::<usize>().ok()?;
+        let max = bounds.next()?.parse::<usize>().ok()?;
+        if bounds.next().is_some() || min > max {
+            return None;
+        }
+        Some(ValueRange { min, max })
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    ranges: Vec<ValueRange>,
+}
+
+impl Rule {
+    pub fn parse(input: &str) -> Option<Self> {
+        let ranges = input
+            .split(" or ")
+            .map(ValueRange::parse)
+            .collect::<Option<Vec<ValueRange>>>()?;
+        Some(Rule { ranges })
+    }
+
+    pub fn matches(&self, value: usize) -> bool {
+        self.ranges.iter().any(|range| range.contains(value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_ranges_with_inclusive_edges() {
+        let range = ValueRange::parse("1-3").unwrap();
+
+        assert_eq!(range, ValueRange::new(1, 3));
+        assert!(range.contains(1) && range.contains(3));
+        assert!(!range.contains(4));
+        assert_eq!(ValueRange::parse("3-1"), None);
+        assert_eq!(ValueRange::parse("1-2-3"), None);
+    }
+
+    #[test]
+    fn rule_matches_any_of_its_ranges() {
+        let rule = Rule::parse("1-3 or 5-7").unwrap();
+
+        assert!(rule.matches(2) && rule.matches(7));
+        assert!(!rule.matches(4));
+        assert_eq!(Rule::parse("1-3 or five"), None);
+    }
+}